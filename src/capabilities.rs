@@ -0,0 +1,440 @@
+//! WMTS 1.0.0 capabilities
+
+use crate::crs::crs_info;
+use crate::tile::BoundingBox;
+use crate::tile_matrix_set::TileMatrixSetError;
+use crate::tms::{bottom_left_origin, meters_per_unit, Result, Tms, TmsError};
+use crate::xml::{self, Element};
+use ogcapi_types::common::{Authority, Crs};
+use ogcapi_types::tiles::{BoundingBox2D, TileMatrix, TileMatrixSet, TitleDescriptionKeywords};
+use std::fmt::Write;
+use std::num::NonZeroU64;
+use std::str::FromStr;
+use std::sync::Arc;
+
+/// Layer of a WMTS capabilities document.
+#[derive(Clone, Debug)]
+pub struct WmtsLayer {
+    pub identifier: String,
+    pub title: String,
+    /// Tile media types, e.g. `image/png`
+    pub formats: Vec<String>,
+    /// Geographic extent of the layer data
+    pub extent: BoundingBox,
+    pub minzoom: u8,
+    pub maxzoom: u8,
+    /// TileMatrixSets the layer is available in
+    pub tile_matrix_sets: Vec<Arc<Tms>>,
+    /// Tile URL template with `{TileMatrix}`, `{TileRow}` and `{TileCol}` placeholders
+    pub resource_url: Option<String>,
+}
+
+impl Tms {
+    /// WMTS `<TileMatrixSet>` element.
+    ///
+    /// Coordinates are written in CRS axis order. WMTS numbers rows from the top left corner,
+    /// tile matrices with a bottom left origin are described with their top left corner.
+    /// Tile matrices with coalesced tiles are not supported by WMTS.
+    pub fn wmts_tile_matrix_set(&self) -> Result<String> {
+        self.check_wmts_support()?;
+        let mut xml = String::new();
+        xml.push_str("<TileMatrixSet>\n");
+        write_title(
+            &mut xml,
+            self.tms.title_description_keywords.title.as_deref(),
+        );
+        let _ = writeln!(
+            xml,
+            "<ows:Identifier>{}</ows:Identifier>",
+            escape(&self.tms.id)
+        );
+        let crs = crs_urn(self.crs());
+        let bbox = self.xy_bbox();
+        let _ = writeln!(
+            xml,
+            "<ows:BoundingBox crs=\"{crs}\">\n<ows:LowerCorner>{}</ows:LowerCorner>\n<ows:UpperCorner>{}</ows:UpperCorner>\n</ows:BoundingBox>",
+            self.axis_ordered(bbox.left, bbox.bottom),
+            self.axis_ordered(bbox.right, bbox.top),
+        );
+        let _ = writeln!(xml, "<ows:SupportedCRS>{crs}</ows:SupportedCRS>");
+        if let Some(wkss) = &self.tms.well_known_scale_set {
+            let _ = writeln!(
+                xml,
+                "<WellKnownScaleSet>{}</WellKnownScaleSet>",
+                escape(&def_urn(wkss))
+            );
+        }
+        for matrix in self.matrices() {
+            let top_left = if bottom_left_origin(matrix) {
+                self.xy_corner(matrix, 0, matrix.matrix_height.into())
+            } else {
+                self.xy_corner(matrix, 0, 0)
+            };
+            let _ = writeln!(
+                xml,
+                "<TileMatrix>\n<ows:Identifier>{}</ows:Identifier>\n<ScaleDenominator>{}</ScaleDenominator>\n<TopLeftCorner>{}</TopLeftCorner>\n<TileWidth>{}</TileWidth>\n<TileHeight>{}</TileHeight>\n<MatrixWidth>{}</MatrixWidth>\n<MatrixHeight>{}</MatrixHeight>\n</TileMatrix>",
+                escape(&matrix.id),
+                matrix.scale_denominator,
+                self.axis_ordered(top_left.x, top_left.y),
+                matrix.tile_width,
+                matrix.tile_height,
+                matrix.matrix_width,
+                matrix.matrix_height,
+            );
+        }
+        xml.push_str("</TileMatrixSet>\n");
+        Ok(xml)
+    }
+
+    /// WMTS `<TileMatrixSetLimits>` element for a geographic extent.
+    ///
+    /// Returns an empty string if no tile matrix overlaps the extent within the zoom levels.
+    fn wmts_tile_matrix_set_limits(
+        &self,
+        extent: &BoundingBox,
+        minzoom: u8,
+        maxzoom: u8,
+    ) -> Result<String> {
+        self.check_wmts_support()?;
+        let minzoom = minzoom.max(self.minzoom());
+        let maxzoom = maxzoom.min(self.maxzoom());
+        let ranges = self.merged_tile_limits(extent, minzoom, maxzoom)?;
+        if ranges.is_empty() {
+            return Ok(String::new());
+        }
+        let mut xml = String::new();
+        xml.push_str("<TileMatrixSetLimits>\n");
+        for range in ranges {
+            let matrix = self.matrix(range.z)?;
+            let matrix = matrix.as_ref();
+            let (min_row, max_row) = if bottom_left_origin(matrix) {
+                let max = u64::from(matrix.matrix_height) - 1;
+                (max - range.y_max, max - range.y_min)
+            } else {
+                (range.y_min, range.y_max)
+            };
+            let _ = writeln!(
+                xml,
+                "<TileMatrixLimits>\n<TileMatrix>{}</TileMatrix>\n<MinTileRow>{min_row}</MinTileRow>\n<MaxTileRow>{max_row}</MaxTileRow>\n<MinTileCol>{}</MinTileCol>\n<MaxTileCol>{}</MaxTileCol>\n</TileMatrixLimits>",
+                escape(&matrix.id),
+                range.x_min,
+                range.x_max,
+            );
+        }
+        xml.push_str("</TileMatrixSetLimits>\n");
+        Ok(xml)
+    }
+
+    /// Check that the tile matrices have no coalesced tiles, which WMTS can't describe.
+    fn check_wmts_support(&self) -> Result<()> {
+        match self
+            .matrices()
+            .iter()
+            .find(|matrix| matrix.variable_matrix_widths.is_some())
+        {
+            Some(matrix) => Err(TmsError::CapabilitiesError(format!(
+                "tile matrix `{}` has coalesced tiles",
+                matrix.id
+            ))),
+            None => Ok(()),
+        }
+    }
+
+    /// Coordinates in CRS axis order.
+    fn axis_ordered(&self, x: f64, y: f64) -> String {
+        if self.invert_axis() {
+            format!("{y} {x}")
+        } else {
+            format!("{x} {y}")
+        }
+    }
+}
+
+impl WmtsLayer {
+    /// WMTS `<Layer>` element.
+    pub fn wmts_layer(&self) -> Result<String> {
+        let mut xml = String::new();
+        xml.push_str("<Layer>\n");
+        write_title(&mut xml, Some(&self.title));
+        let _ = writeln!(
+            xml,
+            "<ows:WGS84BoundingBox>\n<ows:LowerCorner>{} {}</ows:LowerCorner>\n<ows:UpperCorner>{} {}</ows:UpperCorner>\n</ows:WGS84BoundingBox>",
+            self.extent.left, self.extent.bottom, self.extent.right, self.extent.top
+        );
+        let _ = writeln!(
+            xml,
+            "<ows:Identifier>{}</ows:Identifier>",
+            escape(&self.identifier)
+        );
+        xml.push_str(
+            "<Style isDefault=\"true\">\n<ows:Identifier>default</ows:Identifier>\n</Style>\n",
+        );
+        for format in &self.formats {
+            let _ = writeln!(xml, "<Format>{}</Format>", escape(format));
+        }
+        for tms in &self.tile_matrix_sets {
+            let _ = writeln!(
+                xml,
+                "<TileMatrixSetLink>\n<TileMatrixSet>{}</TileMatrixSet>",
+                escape(&tms.tms.id)
+            );
+            xml.push_str(&tms.wmts_tile_matrix_set_limits(
+                &self.extent,
+                self.minzoom,
+                self.maxzoom,
+            )?);
+            xml.push_str("</TileMatrixSetLink>\n");
+        }
+        if let Some(template) = &self.resource_url {
+            for format in &self.formats {
+                let _ = writeln!(
+                    xml,
+                    "<ResourceURL format=\"{}\" resourceType=\"tile\" template=\"{}\"/>",
+                    escape(format),
+                    escape(template)
+                );
+            }
+        }
+        xml.push_str("</Layer>\n");
+        Ok(xml)
+    }
+}
+
+/// WMTS 1.0.0 capabilities document with a `<Contents>` section.
+///
+/// Contains the given layers and all TileMatrixSets they are linked to.
+/// Service metadata sections have to be added by the caller.
+pub fn wmts_capabilities(layers: &[WmtsLayer]) -> Result<String> {
+    let mut xml = String::new();
+    xml.push_str(concat!(
+        "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n",
+        "<Capabilities xmlns=\"http://www.opengis.net/wmts/1.0\" ",
+        "xmlns:ows=\"http://www.opengis.net/ows/1.1\" ",
+        "xmlns:xlink=\"http://www.w3.org/1999/xlink\" ",
+        "xmlns:xsi=\"http://www.w3.org/2001/XMLSchema-instance\" ",
+        "xsi:schemaLocation=\"http://www.opengis.net/wmts/1.0 http://schemas.opengis.net/wmts/1.0/wmtsGetCapabilities_response.xsd\" ",
+        "version=\"1.0.0\">\n",
+        "<Contents>\n"
+    ));
+    let mut tile_matrix_sets: Vec<&Tms> = Vec::new();
+    for layer in layers {
+        xml.push_str(&layer.wmts_layer()?);
+        for tms in &layer.tile_matrix_sets {
+            if !tile_matrix_sets.iter().any(|t| t.tms.id == tms.tms.id) {
+                tile_matrix_sets.push(tms);
+            }
+        }
+    }
+    for tms in tile_matrix_sets {
+        xml.push_str(&tms.wmts_tile_matrix_set()?);
+    }
+    xml.push_str("</Contents>\n</Capabilities>\n");
+    Ok(xml)
+}
+
+/// Read the TileMatrixSets of a WMTS 1.0.0 capabilities document.
+///
+/// CRS URNs (`urn:ogc:def:crs:EPSG::3857`), `EPSG:3857` codes and OGC URIs are supported.
+/// TopLeftCorner is expected in CRS axis order. Longitude first corners of geographic CRS
+/// with latitude first axis order are detected and swapped.
+///
+/// ```rust
+/// use tile_grid::{parse_wmts_tile_matrix_sets, tms, TileMatrixSets};
+///
+/// let tms = tms().lookup("WebMercatorQuad").unwrap();
+/// let xml = format!("<Capabilities><Contents>{}</Contents></Capabilities>", tms.wmts_tile_matrix_set().unwrap());
+/// let sets = parse_wmts_tile_matrix_sets(&xml).unwrap();
+/// let mut registry = TileMatrixSets::new();
+/// registry.register(sets, false).unwrap();
+/// assert!(registry.lookup("WebMercatorQuad").is_ok());
+/// ```
+pub fn parse_wmts_tile_matrix_sets(
+    xml: &str,
+) -> std::result::Result<Vec<TileMatrixSet>, TileMatrixSetError> {
+    let root = xml::parse(xml).map_err(TileMatrixSetError::XmlError)?;
+    let contents = root
+        .child("Contents")
+        .ok_or_else(|| capabilities_error("missing `Contents`"))?;
+    contents
+        .children("TileMatrixSet")
+        .map(tile_matrix_set_from_xml)
+        .collect()
+}
+
+fn tile_matrix_set_from_xml(
+    element: &Element,
+) -> std::result::Result<TileMatrixSet, TileMatrixSetError> {
+    let id = required_text(element, "Identifier")?.to_string();
+    let crs = parse_crs(required_text(element, "SupportedCRS")?)?;
+    let bounding_box = element
+        .child("BoundingBox")
+        .map(|bbox| -> std::result::Result<_, TileMatrixSetError> {
+            Ok(BoundingBox2D {
+                lower_left: parse_point(required_text(bbox, "LowerCorner")?)?,
+                upper_right: parse_point(required_text(bbox, "UpperCorner")?)?,
+                crs: bbox.attribute("crs").map(parse_crs).transpose()?,
+                ordered_axes: None,
+            })
+        })
+        .transpose()?;
+    let tile_matrices = element
+        .children("TileMatrix")
+        .map(|matrix| tile_matrix_from_xml(matrix, &crs))
+        .collect::<std::result::Result<Vec<_>, _>>()?;
+    Ok(TileMatrixSet {
+        title_description_keywords: title_description_keywords(element),
+        id,
+        uri: None,
+        crs,
+        ordered_axes: None,
+        well_known_scale_set: element.child_text("WellKnownScaleSet").map(def_uri),
+        bounding_box,
+        tile_matrices,
+    })
+}
+
+fn tile_matrix_from_xml(
+    element: &Element,
+    crs: &Crs,
+) -> std::result::Result<TileMatrix, TileMatrixSetError> {
+    let scale_denominator: f64 = parse_value(element, "ScaleDenominator")?;
+    let mut point_of_origin = parse_point(required_text(element, "TopLeftCorner")?)?;
+    let lat_first_geographic =
+        crs_info(crs).map_or(false, |info| info.axis_inverted && info.angular_unit);
+    if lat_first_geographic && point_of_origin[0].abs() > 90.0 {
+        point_of_origin.swap(0, 1);
+    }
+    let mpu = meters_per_unit(crs)
+        .map_err(|err| TileMatrixSetError::CapabilitiesError(err.to_string()))?;
+    Ok(TileMatrix {
+        title_description_keywords: title_description_keywords(element),
+        id: required_text(element, "Identifier")?.to_string(),
+        scale_denominator,
+        cell_size: scale_denominator * 0.28e-3 / mpu,
+        corner_of_origin: None,
+        point_of_origin,
+        tile_width: parse_value(element, "TileWidth")?,
+        tile_height: parse_value(element, "TileHeight")?,
+        matrix_width: parse_value::<NonZeroU64>(element, "MatrixWidth")?,
+        matrix_height: parse_value::<NonZeroU64>(element, "MatrixHeight")?,
+        variable_matrix_widths: None,
+    })
+}
+
+fn title_description_keywords(element: &Element) -> TitleDescriptionKeywords {
+    let keywords = element.child("Keywords").map(|keywords| {
+        keywords
+            .children("Keyword")
+            .map(|keyword| keyword.text.trim().to_string())
+            .collect()
+    });
+    TitleDescriptionKeywords {
+        title: element.child_text("Title").map(str::to_string),
+        description: element.child_text("Abstract").map(str::to_string),
+        keywords,
+    }
+}
+
+fn required_text<'a>(
+    element: &'a Element,
+    name: &str,
+) -> std::result::Result<&'a str, TileMatrixSetError> {
+    element
+        .child_text(name)
+        .ok_or_else(|| capabilities_error(&format!("missing `{name}` in `{}`", element.name)))
+}
+
+fn parse_value<T: FromStr>(
+    element: &Element,
+    name: &str,
+) -> std::result::Result<T, TileMatrixSetError> {
+    let text = required_text(element, name)?;
+    text.parse()
+        .map_err(|_| capabilities_error(&format!("invalid `{name}` value `{text}`")))
+}
+
+fn parse_point(text: &str) -> std::result::Result<[f64; 2], TileMatrixSetError> {
+    let coords = text
+        .split_whitespace()
+        .map(f64::from_str)
+        .collect::<std::result::Result<Vec<_>, _>>();
+    match coords.as_deref() {
+        Ok([a, b]) => Ok([*a, *b]),
+        _ => Err(capabilities_error(&format!("invalid coordinates `{text}`"))),
+    }
+}
+
+/// Parse a CRS URN, `EPSG:<code>` or OGC CRS URI.
+fn parse_crs(text: &str) -> std::result::Result<Crs, TileMatrixSetError> {
+    let text = text.trim();
+    let unsupported = || capabilities_error(&format!("unsupported CRS `{text}`"));
+    let urn = text
+        .strip_prefix("urn:ogc:def:crs:")
+        .or_else(|| text.strip_prefix("urn:x-ogc:def:crs:"));
+    let (authority, version, code) = if let Some(urn) = urn {
+        let parts = urn.split(':').collect::<Vec<_>>();
+        match parts[..] {
+            [authority, version, code] => (authority, version, code),
+            [authority, code] => (authority, "", code),
+            _ => return Err(unsupported()),
+        }
+    } else if let Some(code) = text.strip_prefix("EPSG:") {
+        ("EPSG", "", code)
+    } else {
+        return Crs::from_str(text).map_err(|_| unsupported());
+    };
+    let authority = Authority::from_str(authority).map_err(|_| unsupported())?;
+    let version = match (&authority, version) {
+        (Authority::EPSG, _) => "0",
+        (Authority::OGC, "") => "1.3",
+        (Authority::OGC, version) => version,
+    };
+    Ok(Crs::new(authority, version, code))
+}
+
+/// Convert an OGC definition URN into a URI.
+fn def_uri(urn: &str) -> String {
+    match urn.strip_prefix("urn:ogc:def:") {
+        Some(path) => format!("http://www.opengis.net/def/{}", path.replace(':', "/")),
+        None => urn.to_string(),
+    }
+}
+
+fn capabilities_error(msg: &str) -> TileMatrixSetError {
+    TileMatrixSetError::CapabilitiesError(msg.to_string())
+}
+
+fn write_title(xml: &mut String, title: Option<&str>) {
+    if let Some(title) = title {
+        let _ = writeln!(xml, "<ows:Title>{}</ows:Title>", escape(title));
+    }
+}
+
+/// CRS URN as used in WMTS, e.g. `urn:ogc:def:crs:EPSG::3857`.
+fn crs_urn(crs: &Crs) -> String {
+    let version = if crs.version == "0" { "" } else { &crs.version };
+    format!("urn:ogc:def:crs:{}:{version}:{}", crs.authority, crs.code)
+}
+
+/// Convert an OGC definition URI into a URN.
+fn def_urn(uri: &str) -> String {
+    match uri.strip_prefix("http://www.opengis.net/def/") {
+        Some(path) => format!("urn:ogc:def:{}", path.replace('/', ":")),
+        None => uri.to_string(),
+    }
+}
+
+fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&apos;"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
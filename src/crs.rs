@@ -0,0 +1,262 @@
+use ogcapi_types::common::{Authority, Crs};
+use std::f64::consts::PI;
+
+/// Metadata of a coordinate reference system (CRS)
+#[derive(Clone, Debug, PartialEq)]
+pub struct CrsInfo {
+    /// Unit name of the horizontal axes, like "metre" or "degree"
+    pub unit_name: String,
+    /// Unit size in metre for linear units or in radian for angular units
+    pub unit_conv_factor: f64,
+    /// Angular unit of a geographic CRS
+    pub angular_unit: bool,
+    /// First axis is northing or latitude
+    pub axis_inverted: bool,
+    /// Semi-major axis of the ellipsoid in metre
+    pub semi_major_metre: f64,
+}
+
+impl CrsInfo {
+    /// Coefficient to convert the CRS units into meters.
+    pub fn meters_per_unit(&self) -> f64 {
+        if self.angular_unit {
+            // metersPerUnit=2pa/360 for degrees
+            let degrees_per_unit = self.unit_conv_factor / (PI / 180.0);
+            2.0 * PI * self.semi_major_metre / 360.0 * degrees_per_unit
+        } else {
+            self.unit_conv_factor
+        }
+    }
+}
+
+/// Lookup CRS metadata.
+///
+/// With the `projtransform` feature, the CRS definition is queried from PROJ.
+/// Otherwise, or if PROJ doesn't know the CRS, a built-in table of common EPSG codes is used.
+pub fn crs_info(crs: &Crs) -> Option<CrsInfo> {
+    #[cfg(feature = "projtransform")]
+    if let Some(info) = proj_crs_info(crs) {
+        return Some(info);
+    }
+    builtin_crs_info(crs)
+}
+
+// Ellipsoid semi-major axes
+const WGS84: f64 = 6378137.0;
+const GRS80: f64 = 6378137.0;
+const INTERNATIONAL_1924: f64 = 6378388.0;
+const CLARKE_1866: f64 = 6378206.4;
+const BESSEL_1841: f64 = 6377397.155;
+const AIRY_1830: f64 = 6377563.396;
+
+#[derive(Clone, Copy)]
+enum Unit {
+    Metre,
+    Degree,
+    Foot,
+    UsSurveyFoot,
+}
+
+#[derive(Clone, Copy)]
+enum Axes {
+    EastNorth,
+    NorthEast,
+}
+
+/// Known EPSG CRS: (code, unit, axis order, semi-major axis)
+#[rustfmt::skip]
+const EPSG_CRS: &[(u16, Unit, Axes, f64)] = &[
+    // Geographic
+    (4326, Unit::Degree, Axes::NorthEast, WGS84), // WGS 84
+    (4979, Unit::Degree, Axes::NorthEast, WGS84), // WGS 84 (3D)
+    (4258, Unit::Degree, Axes::NorthEast, GRS80), // ETRS89
+    (4269, Unit::Degree, Axes::NorthEast, GRS80), // NAD83
+    (4617, Unit::Degree, Axes::NorthEast, GRS80), // NAD83(CSRS)
+    (4283, Unit::Degree, Axes::NorthEast, GRS80), // GDA94
+    (7844, Unit::Degree, Axes::NorthEast, GRS80), // GDA2020
+    (4167, Unit::Degree, Axes::NorthEast, GRS80), // NZGD2000
+    (4674, Unit::Degree, Axes::NorthEast, GRS80), // SIRGAS 2000
+    (4490, Unit::Degree, Axes::NorthEast, GRS80), // CGCS2000
+    (6668, Unit::Degree, Axes::NorthEast, GRS80), // JGD2011
+    (4230, Unit::Degree, Axes::NorthEast, INTERNATIONAL_1924), // ED50
+    (4267, Unit::Degree, Axes::NorthEast, CLARKE_1866), // NAD27
+    (4314, Unit::Degree, Axes::NorthEast, BESSEL_1841), // DHDN
+    (4277, Unit::Degree, Axes::NorthEast, AIRY_1830), // OSGB36
+    // Projected
+    (3857, Unit::Metre, Axes::EastNorth, WGS84), // WGS 84 / Pseudo-Mercator
+    (3395, Unit::Metre, Axes::EastNorth, WGS84), // WGS 84 / World Mercator
+    (5041, Unit::Metre, Axes::EastNorth, WGS84), // WGS 84 / UPS North (E,N)
+    (5042, Unit::Metre, Axes::EastNorth, WGS84), // WGS 84 / UPS South (E,N)
+    (32661, Unit::Metre, Axes::NorthEast, WGS84), // WGS 84 / UPS North (N,E)
+    (32761, Unit::Metre, Axes::NorthEast, WGS84), // WGS 84 / UPS South (N,E)
+    (3031, Unit::Metre, Axes::EastNorth, WGS84), // WGS 84 / Antarctic Polar Stereographic
+    (3413, Unit::Metre, Axes::EastNorth, WGS84), // WGS 84 / NSIDC Sea Ice Polar Stereographic North
+    (3034, Unit::Metre, Axes::NorthEast, GRS80), // ETRS89-extended / LCC Europe
+    (3035, Unit::Metre, Axes::NorthEast, GRS80), // ETRS89-extended / LAEA Europe
+    (25832, Unit::Metre, Axes::EastNorth, GRS80), // ETRS89 / UTM zone 32N
+    (25833, Unit::Metre, Axes::EastNorth, GRS80), // ETRS89 / UTM zone 33N
+    (2154, Unit::Metre, Axes::EastNorth, GRS80), // RGF93 / Lambert-93
+    (2056, Unit::Metre, Axes::EastNorth, BESSEL_1841), // CH1903+ / LV95
+    (21781, Unit::Metre, Axes::EastNorth, BESSEL_1841), // CH1903 / LV03
+    (31467, Unit::Metre, Axes::NorthEast, BESSEL_1841), // DHDN / 3-degree Gauss-Kruger zone 3
+    (27700, Unit::Metre, Axes::EastNorth, AIRY_1830), // OSGB36 / British National Grid
+    (3006, Unit::Metre, Axes::NorthEast, GRS80), // SWEREF99 TM
+    (2193, Unit::Metre, Axes::NorthEast, GRS80), // NZGD2000 / New Zealand Transverse Mercator 2000
+    (3978, Unit::Metre, Axes::EastNorth, GRS80), // NAD83 / Canada Atlas Lambert
+    (5070, Unit::Metre, Axes::EastNorth, GRS80), // NAD83 / Conus Albers
+    (2222, Unit::Foot, Axes::EastNorth, GRS80), // NAD83 / Arizona East (ft)
+    (2223, Unit::Foot, Axes::EastNorth, GRS80), // NAD83 / Arizona Central (ft)
+    (2224, Unit::Foot, Axes::EastNorth, GRS80), // NAD83 / Arizona West (ft)
+    (2227, Unit::UsSurveyFoot, Axes::EastNorth, GRS80), // NAD83 / California zone 3 (ftUS)
+    (2229, Unit::UsSurveyFoot, Axes::EastNorth, GRS80), // NAD83 / California zone 5 (ftUS)
+    (2249, Unit::UsSurveyFoot, Axes::EastNorth, GRS80), // NAD83 / Massachusetts Mainland (ftUS)
+    (2263, Unit::UsSurveyFoot, Axes::EastNorth, GRS80), // NAD83 / New York Long Island (ftUS)
+    (2272, Unit::UsSurveyFoot, Axes::EastNorth, GRS80), // NAD83 / Pennsylvania South (ftUS)
+    (2276, Unit::UsSurveyFoot, Axes::EastNorth, GRS80), // NAD83 / Texas North Central (ftUS)
+    (2278, Unit::UsSurveyFoot, Axes::EastNorth, GRS80), // NAD83 / Texas South Central (ftUS)
+    (3435, Unit::UsSurveyFoot, Axes::EastNorth, GRS80), // NAD83 / Illinois East (ftUS)
+];
+
+fn builtin_crs_info(crs: &Crs) -> Option<CrsInfo> {
+    let (unit, axes, semi_major_metre) = match crs.authority {
+        Authority::OGC => match crs.code.as_str() {
+            "CRS84" | "CRS84h" => (Unit::Degree, Axes::EastNorth, WGS84),
+            _ => return None,
+        },
+        Authority::EPSG => {
+            let code = crs.code.parse::<u16>().ok()?;
+            match code {
+                // WGS 84 / UTM zones
+                32601..=32660 | 32701..=32760 => (Unit::Metre, Axes::EastNorth, WGS84),
+                _ => EPSG_CRS
+                    .iter()
+                    .find(|(epsg, ..)| *epsg == code)
+                    .map(|(_, unit, axes, semi_major)| (*unit, *axes, *semi_major))?,
+            }
+        }
+    };
+    let (unit_name, unit_conv_factor) = match unit {
+        Unit::Metre => ("metre", 1.0),
+        Unit::Degree => ("degree", PI / 180.0),
+        Unit::Foot => ("foot", 0.3048),
+        Unit::UsSurveyFoot => ("US survey foot", 0.30480060960121924),
+    };
+    Some(CrsInfo {
+        unit_name: unit_name.to_string(),
+        unit_conv_factor,
+        angular_unit: matches!(unit, Unit::Degree),
+        axis_inverted: matches!(axes, Axes::NorthEast),
+        semi_major_metre,
+    })
+}
+
+/// Query PROJ once per CRS and cache the result, including unknown CRS.
+#[cfg(feature = "projtransform")]
+fn proj_crs_info(crs: &Crs) -> Option<CrsInfo> {
+    use once_cell::sync::Lazy;
+    use std::collections::HashMap;
+    use std::sync::{Mutex, PoisonError};
+
+    static CACHE: Lazy<Mutex<HashMap<Crs, Option<CrsInfo>>>> =
+        Lazy::new(|| Mutex::new(HashMap::new()));
+
+    let mut cache = CACHE.lock().unwrap_or_else(PoisonError::into_inner);
+    cache
+        .entry(crs.clone())
+        .or_insert_with(|| {
+            let definition = std::ffi::CString::new(crs.as_known_crs()).ok()?;
+            unsafe {
+                let ctx = proj_sys::proj_context_create();
+                let info = query_crs_info(ctx, &definition);
+                proj_sys::proj_context_destroy(ctx);
+                info
+            }
+        })
+        .clone()
+}
+
+#[cfg(feature = "projtransform")]
+unsafe fn query_crs_info(
+    ctx: *mut proj_sys::PJ_CONTEXT,
+    definition: &std::ffi::CStr,
+) -> Option<CrsInfo> {
+    use proj_sys::*;
+    use std::ffi::CStr;
+    use std::os::raw::c_char;
+    use std::ptr;
+
+    let pj = proj_create(ctx, definition.as_ptr());
+    if pj.is_null() {
+        return None;
+    }
+    let cs = proj_crs_get_coordinate_system(ctx, pj);
+    let ellipsoid = proj_get_ellipsoid(ctx, pj);
+    let mut info = None;
+    if !cs.is_null() && !ellipsoid.is_null() && proj_cs_get_axis_count(ctx, cs) >= 2 {
+        let mut direction: *const c_char = ptr::null();
+        let mut unit_conv_factor = 0.0;
+        let mut unit_name: *const c_char = ptr::null();
+        let mut semi_major_metre = 0.0;
+        if proj_cs_get_axis_info(
+            ctx,
+            cs,
+            0,
+            ptr::null_mut(),
+            ptr::null_mut(),
+            &mut direction,
+            &mut unit_conv_factor,
+            &mut unit_name,
+            ptr::null_mut(),
+            ptr::null_mut(),
+        ) != 0
+            && proj_ellipsoid_get_parameters(
+                ctx,
+                ellipsoid,
+                &mut semi_major_metre,
+                ptr::null_mut(),
+                ptr::null_mut(),
+                ptr::null_mut(),
+            ) != 0
+        {
+            let direction = CStr::from_ptr(direction).to_string_lossy().to_lowercase();
+            info = Some(CrsInfo {
+                unit_name: CStr::from_ptr(unit_name).to_string_lossy().into_owned(),
+                unit_conv_factor,
+                angular_unit: proj_cs_get_type(ctx, cs)
+                    == PJ_COORDINATE_SYSTEM_TYPE_PJ_CS_TYPE_ELLIPSOIDAL,
+                axis_inverted: direction == "north" || direction == "south",
+                semi_major_metre,
+            });
+        }
+    }
+    proj_destroy(ellipsoid);
+    proj_destroy(cs);
+    proj_destroy(pj);
+    info
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn builtin_lookup() {
+        let info = builtin_crs_info(&Crs::from_epsg(4258)).unwrap();
+        assert_eq!(info.unit_name, "degree");
+        assert!(info.axis_inverted);
+        assert_eq!(info.meters_per_unit(), 2.0 * PI * 6378137.0 / 360.0);
+
+        let info = builtin_crs_info(&Crs::default()).unwrap();
+        assert!(!info.axis_inverted);
+
+        let info = builtin_crs_info(&Crs::from_epsg(32631)).unwrap();
+        assert_eq!(info.meters_per_unit(), 1.0);
+        assert!(!info.axis_inverted);
+
+        let info = builtin_crs_info(&Crs::from_epsg(2263)).unwrap();
+        assert_eq!(info.unit_name, "US survey foot");
+        assert_eq!(info.meters_per_unit(), 0.30480060960121924);
+
+        assert_eq!(builtin_crs_info(&Crs::from_epsg(4000)), None);
+    }
+}
@@ -0,0 +1,134 @@
+//! Space-filling curves
+
+/// Space-filling curve through the cells of a square grid.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Curve {
+    /// Hilbert curve, starting at the top left cell and ending at the top right cell.
+    Hilbert,
+    /// Z-order curve, interleaving the bits of column and row.
+    Morton,
+}
+
+impl Curve {
+    /// Position of cell `x`, `y` on the curve through a grid of `2^order` x `2^order` cells.
+    ///
+    /// The order must not exceed 32.
+    pub fn index(&self, order: u8, x: u64, y: u64) -> u64 {
+        debug_assert!(order <= 32);
+        match self {
+            Curve::Hilbert => hilbert_index(order, x, y),
+            Curve::Morton => spread_bits(x) | (spread_bits(y) << 1),
+        }
+    }
+
+    /// Cell `(x, y)` at position `index` on the curve through a grid of `2^order` x `2^order` cells.
+    ///
+    /// The order must not exceed 32.
+    pub fn cell(&self, order: u8, index: u64) -> (u64, u64) {
+        debug_assert!(order <= 32);
+        match self {
+            Curve::Hilbert => hilbert_cell(order, index),
+            Curve::Morton => (compact_bits(index), compact_bits(index >> 1)),
+        }
+    }
+}
+
+// Hilbert curve conversions from <https://en.wikipedia.org/wiki/Hilbert_curve>
+
+fn hilbert_index(order: u8, mut x: u64, mut y: u64) -> u64 {
+    let n = 1u64 << order;
+    let mut index = 0;
+    let mut s = n / 2;
+    while s > 0 {
+        let rx = u64::from(x & s > 0);
+        let ry = u64::from(y & s > 0);
+        index += s * s * ((3 * rx) ^ ry);
+        hilbert_rotate(n, &mut x, &mut y, rx, ry);
+        s /= 2;
+    }
+    index
+}
+
+fn hilbert_cell(order: u8, index: u64) -> (u64, u64) {
+    let n = 1u64 << order;
+    let (mut x, mut y) = (0, 0);
+    let mut t = index;
+    let mut s = 1;
+    while s < n {
+        let rx = 1 & (t / 2);
+        let ry = 1 & (t ^ rx);
+        hilbert_rotate(s, &mut x, &mut y, rx, ry);
+        x += s * rx;
+        y += s * ry;
+        t /= 4;
+        s *= 2;
+    }
+    (x, y)
+}
+
+/// Rotate and flip a quadrant.
+fn hilbert_rotate(n: u64, x: &mut u64, y: &mut u64, rx: u64, ry: u64) {
+    if ry == 0 {
+        if rx == 1 {
+            *x = n - 1 - *x;
+            *y = n - 1 - *y;
+        }
+        std::mem::swap(x, y);
+    }
+}
+
+/// Insert a zero bit after each of the lower 32 bits.
+fn spread_bits(v: u64) -> u64 {
+    let mut v = v & 0x0000_0000_ffff_ffff;
+    v = (v | (v << 16)) & 0x0000_ffff_0000_ffff;
+    v = (v | (v << 8)) & 0x00ff_00ff_00ff_00ff;
+    v = (v | (v << 4)) & 0x0f0f_0f0f_0f0f_0f0f;
+    v = (v | (v << 2)) & 0x3333_3333_3333_3333;
+    (v | (v << 1)) & 0x5555_5555_5555_5555
+}
+
+/// Inverse of `spread_bits`, collecting every second bit.
+fn compact_bits(v: u64) -> u64 {
+    let mut v = v & 0x5555_5555_5555_5555;
+    v = (v | (v >> 1)) & 0x3333_3333_3333_3333;
+    v = (v | (v >> 2)) & 0x0f0f_0f0f_0f0f_0f0f;
+    v = (v | (v >> 4)) & 0x00ff_00ff_00ff_00ff;
+    v = (v | (v >> 8)) & 0x0000_ffff_0000_ffff;
+    (v | (v >> 16)) & 0x0000_0000_ffff_ffff
+}
+
+#[cfg(test)]
+mod test {
+    use super::Curve;
+
+    #[test]
+    fn curve_indices() {
+        let cells = |curve: Curve, order| {
+            (0..1u64 << (2 * order))
+                .map(|index| curve.cell(order, index))
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(
+            cells(Curve::Hilbert, 1),
+            vec![(0, 0), (0, 1), (1, 1), (1, 0)]
+        );
+        assert_eq!(
+            cells(Curve::Morton, 1),
+            vec![(0, 0), (1, 0), (0, 1), (1, 1)]
+        );
+        assert_eq!(
+            cells(Curve::Hilbert, 2)[..6],
+            [(0, 0), (1, 0), (1, 1), (0, 1), (0, 2), (0, 3)]
+        );
+
+        for curve in [Curve::Hilbert, Curve::Morton] {
+            for order in [0, 3, 5] {
+                for (index, (x, y)) in cells(curve, order).into_iter().enumerate() {
+                    assert_eq!(curve.index(order, x, y), index as u64);
+                }
+            }
+            let max = (1 << 32) - 1;
+            assert_eq!(curve.cell(32, curve.index(32, max, 12345)), (max, 12345));
+        }
+    }
+}
@@ -8,7 +8,7 @@
 //! let tms = tms().lookup("WebMercatorQuad").unwrap();
 //!
 //! // Get the bounds for tile Z=4, X=10, Y=10 in the input projection
-//! let bounds = tms.xy_bounds(&Xyz::new(10, 10, 4));
+//! let bounds = tms.xy_bounds(&Xyz::new(10, 10, 4)).unwrap();
 //! assert_eq!(
 //!     bounds,
 //!     BoundingBox::new(
@@ -36,22 +36,38 @@
 //! assert_eq!(tile, Xyz::new(15, 10, 4));
 //!
 //! // Or using coordinates in input CRS
-//! let tile = tms.xy_tile(17734308.1, -5160979.4, 4);
+//! let tile = tms.xy_tile(17734308.1, -5160979.4, 4).unwrap();
 //! assert_eq!(tile, Xyz::new(15, 10, 4));
 //! ```
 
+mod capabilities;
+mod crs;
+mod curve;
+mod pmtiles;
 mod quadkey;
 mod registry;
 mod tile;
 mod tile_matrix_set;
+mod tile_range;
+mod tilejson;
+mod tileset;
 mod tms;
 mod tms_iterator;
 mod transform;
+mod url_template;
 mod wmts;
+mod xml;
 
+pub use capabilities::*;
+pub use crs::*;
+pub use curve::*;
+pub use quadkey::*;
 pub use registry::{RegistryError as Error, *};
 pub use tile::*;
 pub use tile_matrix_set::*;
+pub use tile_range::*;
+pub use tilejson::*;
 pub use tms::*;
 pub use tms_iterator::*;
+pub use url_template::*;
 pub use wmts::*;
@@ -0,0 +1,119 @@
+use crate::curve::Curve;
+use crate::tile::Xyz;
+use crate::tile_range::TileRange;
+use crate::tms::{Result, Tms, TmsError};
+use std::ops::RangeInclusive;
+
+/// Highest zoom level with PMTiles tile IDs fitting into 64 bits
+const PMTILES_MAX_ZOOM: u8 = 31;
+
+impl Tms {
+    /// Get the PMTiles v3 tile ID of a tile
+    ///
+    /// The tile ID is the number of tiles on all lower zoom levels plus the Hilbert index of
+    /// the tile within its zoom level.
+    /// Only quadtree tile matrix sets with `2^z` x `2^z` tiles on zoom level `z` are supported.
+    pub fn pmtiles_tile_id(&self, tile: &Xyz) -> Result<u64> {
+        self.check_pmtiles_zoom(tile.z)?;
+        let size = 1u64 << tile.z;
+        if tile.x >= size || tile.y >= size {
+            return Err(TmsError::PmTilesError(format!(
+                "Tile {tile} outside of tile matrix"
+            )));
+        }
+        Ok(zoom_base_id(tile.z) + Curve::Hilbert.index(tile.z, tile.x, tile.y))
+    }
+
+    /// Get the tile corresponding to a PMTiles v3 tile ID
+    pub fn pmtiles_tile(&self, tile_id: u64) -> Result<Xyz> {
+        let z = (1..=PMTILES_MAX_ZOOM + 1)
+            .find(|z| tile_id < zoom_base_id(*z))
+            .map(|z| z - 1)
+            .ok_or_else(|| TmsError::PmTilesError(format!("Tile ID {tile_id} too large")))?;
+        self.check_pmtiles_zoom(z)?;
+        let (x, y) = Curve::Hilbert.cell(z, tile_id - zoom_base_id(z));
+        Ok(Xyz::new(x, y, z))
+    }
+
+    /// Get the PMTiles v3 tile IDs of a tile range
+    ///
+    /// Returns ascending, non-adjacent runs of consecutive tile IDs.
+    pub fn pmtiles_tile_ids(&self, range: &TileRange) -> Result<Vec<RangeInclusive<u64>>> {
+        self.check_pmtiles_zoom(range.z)?;
+        if range.x_min > range.x_max
+            || range.y_min > range.y_max
+            || range.x_max >> range.z > 0
+            || range.y_max >> range.z > 0
+        {
+            return Err(TmsError::PmTilesError(format!(
+                "Tile range cols {}-{}, rows {}-{} outside of tile matrix",
+                range.x_min, range.x_max, range.y_min, range.y_max
+            )));
+        }
+        let mut runs = Vec::new();
+        collect_runs(range, 0, 0, range.z, &mut runs);
+        let base = zoom_base_id(range.z);
+        Ok(runs
+            .into_iter()
+            .map(|run| run.start() + base..=run.end() + base)
+            .collect())
+    }
+
+    fn check_pmtiles_zoom(&self, zoom: u8) -> Result<()> {
+        if !self.is_quadtree {
+            return Err(TmsError::NoQuadkeySupport);
+        }
+        if zoom > PMTILES_MAX_ZOOM {
+            return Err(TmsError::PmTilesError(format!(
+                "Zoom level {zoom} exceeds {PMTILES_MAX_ZOOM}"
+            )));
+        }
+        let matrix = self.matrix(zoom)?;
+        let matrix = matrix.as_ref();
+        let size = 1u64 << zoom;
+        if u64::from(matrix.matrix_width) != size || u64::from(matrix.matrix_height) != size {
+            return Err(TmsError::PmTilesError(format!(
+                "Tile matrix `{}` has {}x{} tiles instead of {size}x{size}",
+                matrix.id, matrix.matrix_width, matrix.matrix_height
+            )));
+        }
+        Ok(())
+    }
+}
+
+/// Number of tiles on all zoom levels below `zoom`.
+fn zoom_base_id(zoom: u8) -> u64 {
+    (((1u128 << (2 * u32::from(zoom))) - 1) / 3) as u64
+}
+
+/// Collect the Hilbert index runs of the tiles in `range` within the block of `2^level` x `2^level`
+/// tiles at `x`, `y`.
+fn collect_runs(range: &TileRange, x: u64, y: u64, level: u8, runs: &mut Vec<RangeInclusive<u64>>) {
+    let size = 1u64 << level;
+    let (x_max, y_max) = (x + size - 1, y + size - 1);
+    if x > range.x_max || x_max < range.x_min || y > range.y_max || y_max < range.y_min {
+        return;
+    }
+    let start = Curve::Hilbert.index(range.z, x, y) >> (2 * level) << (2 * level);
+    if x >= range.x_min && x_max <= range.x_max && y >= range.y_min && y_max <= range.y_max {
+        let end = start + (size * size - 1);
+        match runs.last_mut() {
+            Some(last) if *last.end() + 1 == start => *last = *last.start()..=end,
+            _ => runs.push(start..=end),
+        }
+        return;
+    }
+    // Visit the quadrants in curve order
+    let half = size / 2;
+    let mut quadrants = [(0, 0), (half, 0), (0, half), (half, half)].map(|(dx, dy)| {
+        (
+            Curve::Hilbert.index(range.z, x + dx, y + dy),
+            x + dx,
+            y + dy,
+        )
+    });
+    quadrants.sort_by_key(|(index, _, _)| *index);
+    for (_, x, y) in quadrants {
+        collect_runs(range, x, y, level - 1, runs);
+    }
+}
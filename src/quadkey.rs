@@ -1,5 +1,8 @@
-use crate::{tile::Xyz, tms::Tms};
+use crate::curve::Curve;
+use crate::tile::Xyz;
+use crate::tms::{Result, Tms, TmsError};
 use ogcapi_types::tiles::TileMatrix;
+use serde::{Deserialize, Serialize};
 
 /// Check if a number is a power of 2
 fn is_power_of_two(number: u64) -> bool {
@@ -20,16 +23,22 @@ impl Tms {
     ///
     /// # Arguments
     /// * `tile` : instance of Tile
-    pub fn quadkey(&self, tile: &Xyz) -> String {
+    pub fn quadkey(&self, tile: &Xyz) -> Result<String> {
         if !self.is_quadtree {
-            panic!("This Tile Matrix Set doesn't support 2 x 2 quadkeys.");
+            return Err(TmsError::NoQuadkeySupport);
+        }
+        if tile.z > 64 {
+            return Err(TmsError::QuadKeyError(format!(
+                "Zoom level {} exceeds 64 digits",
+                tile.z
+            )));
         }
 
         let t = tile;
-        let mut qk = vec![];
+        let mut qk = String::with_capacity(t.z.into());
         // for z in range(t.z, self.minzoom, -1)
         for z in (self.minzoom() + 1..=t.z).rev() {
-            let mut digit = 0;
+            let mut digit = b'0';
             let mask = 1 << (z - 1);
             if t.x & mask != 0 {
                 digit += 1;
@@ -37,23 +46,28 @@ impl Tms {
             if t.y & mask != 0 {
                 digit += 2;
             }
-            qk.push(digit.to_string());
+            qk.push(digit.into());
         }
 
-        qk.join("")
+        Ok(qk)
     }
 
     /// Get the tile corresponding to a quadkey
     ///
     /// # Arguments
     /// * `qk` - A quadkey string.
-    pub fn quadkey_to_tile(&self, qk: &str) -> Xyz {
+    pub fn quadkey_to_tile(&self, qk: &str) -> Result<Xyz> {
         if !self.is_quadtree {
-            panic!("This Tile Matrix Set doesn't support 2 x 2 quadkeys.");
+            return Err(TmsError::NoQuadkeySupport);
         }
 
         if qk.is_empty() {
-            return Xyz::new(0, 0, 0);
+            return Ok(Xyz::new(0, 0, 0));
+        }
+        if qk.len() > 64 {
+            return Err(TmsError::QuadKeyError(format!(
+                "Quadkey `{qk}` exceeds 64 digits"
+            )));
         }
 
         let mut xtile = 0;
@@ -70,10 +84,192 @@ impl Tms {
                 xtile |= mask;
                 ytile |= mask;
             } else if digit != '0' {
-                panic!("Unexpected quadkey digit: {}", digit);
+                return Err(TmsError::QuadKeyError(format!(
+                    "Unexpected quadkey digit: `{digit}`"
+                )));
             }
         }
 
-        Xyz::new(xtile, ytile, z + 1)
+        Ok(Xyz::new(xtile, ytile, z + 1))
+    }
+
+    /// Get the Quadbin cell of a tile
+    pub fn quadbin(&self, tile: &Xyz) -> Result<Quadbin> {
+        if !self.is_quadtree {
+            return Err(TmsError::NoQuadkeySupport);
+        }
+        Quadbin::from_tile(tile)
+    }
+
+    /// Get the tile corresponding to a Quadbin cell
+    pub fn quadbin_to_tile(&self, quadbin: Quadbin) -> Result<Xyz> {
+        if !self.is_quadtree {
+            return Err(TmsError::NoQuadkeySupport);
+        }
+        quadbin.to_tile()
+    }
+}
+
+const QUADBIN_HEADER: u64 = 0x4000_0000_0000_0000;
+const QUADBIN_MODE_CELL: u64 = 1 << 59;
+const QUADBIN_RESERVED_MASK: u64 = 0x3 << 57;
+const QUADBIN_ZOOM_MASK: u64 = 0x1f << 52;
+const QUADBIN_FOOTER: u64 = 0x000f_ffff_ffff_ffff;
+const QUADBIN_MAX_ZOOM: u8 = 26;
+
+/// CARTO Quadbin cell index
+///
+/// See <https://docs.carto.com/data-and-analysis/analytics-toolbox-for-bigquery/sql-reference/quadbin>
+///
+/// The index contains a header, the zoom level in 5 bits and the interleaved tile column and row
+/// bits, followed by bits set to 1. The maximum zoom level is 26.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+pub struct Quadbin(pub u64);
+
+impl Quadbin {
+    /// Quadbin cell of a tile.
+    pub fn from_tile(tile: &Xyz) -> Result<Self> {
+        check_tile(tile, QUADBIN_MAX_ZOOM)?;
+        let z = u64::from(tile.z);
+        let morton = Curve::Morton.index(tile.z, tile.x, tile.y);
+        Ok(Quadbin(
+            QUADBIN_HEADER
+                | QUADBIN_MODE_CELL
+                | (z << 52)
+                | (morton << (52 - 2 * z))
+                | (QUADBIN_FOOTER >> (2 * z)),
+        ))
+    }
+
+    /// Tile of the cell.
+    pub fn to_tile(self) -> Result<Xyz> {
+        if !self.is_valid() {
+            return Err(TmsError::QuadKeyError(format!(
+                "Invalid Quadbin cell `{:#x}`",
+                self.0
+            )));
+        }
+        let z = self.zoom();
+        let morton = (self.0 & QUADBIN_FOOTER) >> (52 - 2 * u32::from(z));
+        let (x, y) = Curve::Morton.cell(z, morton);
+        Ok(Xyz::new(x, y, z))
+    }
+
+    /// Check header, reserved bits, zoom level and unused bits.
+    pub fn is_valid(self) -> bool {
+        let z = self.zoom();
+        let unused = QUADBIN_FOOTER >> (2 * u32::from(z.min(QUADBIN_MAX_ZOOM)));
+        self.0 >> 59 == (QUADBIN_HEADER | QUADBIN_MODE_CELL) >> 59
+            && self.0 & QUADBIN_RESERVED_MASK == 0
+            && z <= QUADBIN_MAX_ZOOM
+            && self.0 & unused == unused
+    }
+
+    /// Zoom level of the cell.
+    pub fn zoom(self) -> u8 {
+        ((self.0 & QUADBIN_ZOOM_MASK) >> 52) as u8
+    }
+
+    /// Cell containing this cell on the next lower zoom level.
+    ///
+    /// Returns `None` for cells on zoom level 0.
+    pub fn parent(self) -> Option<Self> {
+        let z = u64::from(self.zoom().checked_sub(1)?);
+        Some(Quadbin(
+            (self.0 & !QUADBIN_ZOOM_MASK) | (z << 52) | (QUADBIN_FOOTER >> (2 * z)),
+        ))
+    }
+
+    /// Cells contained in this cell on the next higher zoom level, in Z-order.
+    ///
+    /// Returns `None` for cells on the maximum zoom level.
+    pub fn children(self) -> Option<[Self; 4]> {
+        let z = self.zoom();
+        if z >= QUADBIN_MAX_ZOOM {
+            return None;
+        }
+        let child_z = u64::from(z) + 1;
+        let shift = 52 - 2 * child_z;
+        let base = (self.0 & !QUADBIN_ZOOM_MASK & !(QUADBIN_FOOTER >> (2 * u64::from(z))))
+            | (child_z << 52)
+            | (QUADBIN_FOOTER >> (2 * child_z));
+        Some([0, 1, 2, 3].map(|digit| Quadbin(base & !(3 << shift) | (digit << shift))))
+    }
+}
+
+const TILE_KEY_MAX_ZOOM: u8 = 29;
+const TILE_KEY_COORD_MASK: u64 = (1 << 29) - 1;
+
+/// Tile index packed into 64 bits
+///
+/// The zoom level is stored in the upper 6 bits, followed by 29 bits for the column and 29 bits
+/// for the row. Keys sort like tiles, by zoom level, column and row. The maximum zoom level is 29.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+pub struct TileKey(pub u64);
+
+impl TileKey {
+    /// Packed key of a tile.
+    pub fn from_tile(tile: &Xyz) -> Result<Self> {
+        check_tile(tile, TILE_KEY_MAX_ZOOM)?;
+        Ok(TileKey::pack(tile.z, tile.x, tile.y))
+    }
+
+    /// Tile of the key.
+    pub fn to_tile(self) -> Result<Xyz> {
+        let tile = Xyz::new(
+            (self.0 >> 29) & TILE_KEY_COORD_MASK,
+            self.0 & TILE_KEY_COORD_MASK,
+            self.zoom(),
+        );
+        check_tile(&tile, TILE_KEY_MAX_ZOOM)?;
+        Ok(tile)
+    }
+
+    /// Zoom level of the key.
+    pub fn zoom(self) -> u8 {
+        (self.0 >> 58) as u8
+    }
+
+    /// Key of the parent tile on the next lower zoom level.
+    ///
+    /// Returns `None` for tiles on zoom level 0.
+    pub fn parent(self) -> Option<Self> {
+        let z = self.zoom().checked_sub(1)?;
+        let x = (self.0 >> 29) & TILE_KEY_COORD_MASK;
+        let y = self.0 & TILE_KEY_COORD_MASK;
+        Some(TileKey::pack(z, x >> 1, y >> 1))
+    }
+
+    /// Keys of the child tiles on the next higher zoom level, in Z-order.
+    ///
+    /// Returns `None` for tiles on the maximum zoom level.
+    pub fn children(self) -> Option<[Self; 4]> {
+        let z = self.zoom();
+        if z >= TILE_KEY_MAX_ZOOM {
+            return None;
+        }
+        let x = ((self.0 >> 29) & TILE_KEY_COORD_MASK) << 1;
+        let y = (self.0 & TILE_KEY_COORD_MASK) << 1;
+        Some([(0, 0), (1, 0), (0, 1), (1, 1)].map(|(dx, dy)| TileKey::pack(z + 1, x + dx, y + dy)))
+    }
+
+    fn pack(z: u8, x: u64, y: u64) -> Self {
+        TileKey((u64::from(z) << 58) | (x << 29) | y)
+    }
+}
+
+/// Check zoom level and tile matrix bounds of a quadtree tile.
+fn check_tile(tile: &Xyz, max_zoom: u8) -> Result<()> {
+    if tile.z > max_zoom {
+        return Err(TmsError::QuadKeyError(format!(
+            "Zoom level {} exceeds {max_zoom}",
+            tile.z
+        )));
+    }
+    if tile.x >> tile.z > 0 || tile.y >> tile.z > 0 {
+        return Err(TmsError::QuadKeyError(format!(
+            "Tile {tile} outside of quadtree"
+        )));
     }
+    Ok(())
 }
@@ -1,15 +1,16 @@
-use crate::tile_matrix_set::TileMatrixSetOps;
+use crate::tile_matrix_set::{TileMatrixSetError, TileMatrixSetOps};
 use crate::tms::Tms;
 use ogcapi_types::tiles::TileMatrixSet;
 use once_cell::sync::OnceCell;
 use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, PoisonError, RwLock};
 
 /// Registry of tile matrix sets
 #[derive(Clone)]
 pub struct TileMatrixSets {
-    // Registry containing Tms is not supported because of Proj:
-    // trait `Send` is not implemented for `*mut proj_sys::PJ_AREA`
-    coll: HashMap<String, TileMatrixSet>,
+    // Initialized on registration, shared by lookups
+    coll: HashMap<String, Arc<Tms>>,
 }
 
 #[derive(thiserror::Error, Debug)]
@@ -20,6 +21,10 @@ pub enum RegistryError {
     TmsAlreadyRegistered(String),
     #[error(transparent)]
     TmsError(#[from] crate::tms::TmsError),
+    #[error(transparent)]
+    TileMatrixSetError(#[from] TileMatrixSetError),
+    #[error("{0}: {1}")]
+    DirectoryError(PathBuf, #[source] std::io::Error),
 }
 
 impl Default for TileMatrixSets {
@@ -36,56 +41,157 @@ impl TileMatrixSets {
     }
 
     pub fn get(&self, id: &str) -> Result<&TileMatrixSet, RegistryError> {
+        self.lookup_ref(id).map(|tms| &tms.tms)
+    }
+
+    /// Shared handle of an initialized TileMatrixSet.
+    pub fn lookup(&self, id: &str) -> Result<Arc<Tms>, RegistryError> {
+        self.lookup_ref(id).cloned()
+    }
+
+    fn lookup_ref(&self, id: &str) -> Result<&Arc<Tms>, RegistryError> {
         self.coll
             .get(id)
             .ok_or(RegistryError::TmsNotFound(id.to_string()))
     }
 
-    pub fn lookup(&self, id: &str) -> Result<Tms, RegistryError> {
-        self.get(id)?.try_into().map_err(Into::into)
-    }
-
     pub fn list(&self) -> impl Iterator<Item = &String> {
         self.coll.keys()
     }
 
+    /// Register TileMatrixSets, replacing existing sets with the same id if `overwrite` is set.
     pub fn register(
         &mut self,
         custom_tms: Vec<TileMatrixSet>,
         overwrite: bool,
     ) -> Result<(), RegistryError> {
         for tms in custom_tms {
-            if self.coll.contains_key(&tms.id) {
-                if overwrite {
-                    self.coll.insert(tms.id.clone(), tms);
-                } else {
-                    return Err(RegistryError::TmsAlreadyRegistered(tms.id));
-                }
-            } else {
-                self.coll.insert(tms.id.clone(), tms);
+            if self.coll.contains_key(&tms.id) && !overwrite {
+                return Err(RegistryError::TmsAlreadyRegistered(tms.id));
             }
+            self.coll.insert(tms.id.clone(), Arc::new(Tms::init(&tms)?));
         }
         Ok(())
     }
+
+    /// Remove a registered TileMatrixSet.
+    pub fn remove(&mut self, id: &str) -> Result<TileMatrixSet, RegistryError> {
+        self.coll
+            .remove(id)
+            .map(|tms| tms.tms.clone())
+            .ok_or(RegistryError::TmsNotFound(id.to_string()))
+    }
+
+    /// Register all TileMatrixSets in `*.json` files of a directory.
+    ///
+    /// Files which can't be parsed or registered are skipped. Their errors are returned
+    /// together with the file path.
+    pub fn register_dir<P: AsRef<Path>>(
+        &mut self,
+        dir: P,
+        overwrite: bool,
+    ) -> Result<Vec<(PathBuf, RegistryError)>, RegistryError> {
+        let dir = dir.as_ref();
+        let mut paths = dir
+            .read_dir()
+            .map_err(|e| RegistryError::DirectoryError(dir.into(), e))?
+            .filter_map(|entry| entry.ok())
+            .map(|entry| entry.path())
+            .filter(|p| p.is_file() && p.extension().unwrap_or_default() == "json")
+            .collect::<Vec<_>>();
+        paths.sort();
+        let mut errors = Vec::new();
+        for path in paths {
+            if let Err(e) = self.register_file(&path, overwrite) {
+                errors.push((path, e));
+            }
+        }
+        Ok(errors)
+    }
+
+    fn register_file(&mut self, path: &Path, overwrite: bool) -> Result<(), RegistryError> {
+        let tms = TileMatrixSet::from_json_file(&path.to_string_lossy())?;
+        self.register(vec![tms], overwrite)
+    }
 }
 
 /// Global registry of tile matrix sets
-pub fn tms() -> &'static TileMatrixSets {
-    static TMS: OnceCell<TileMatrixSets> = OnceCell::new();
+///
+/// Includes the TileMatrixSets of all `*.json` files in the directory given by the
+/// environment variable `TILEMATRIXSET_DIRECTORY`. These may overwrite built-in sets.
+/// Errors of files which can't be loaded are returned by [tms_load_errors].
+///
+/// Returns a snapshot of the registry, which isn't affected by later calls of
+/// [register_global] or [remove_global].
+pub fn tms() -> Arc<TileMatrixSets> {
+    global_tms()
+        .read()
+        .unwrap_or_else(PoisonError::into_inner)
+        .clone()
+}
+
+/// Register TileMatrixSets in the global registry, replacing existing sets with the same id if
+/// `overwrite` is set.
+///
+/// Either all or none of the TileMatrixSets are registered.
+///
+/// ```rust
+/// use tile_grid::{register_global, remove_global, tms};
+///
+/// let mut custom = tms().get("WebMercatorQuad").unwrap().clone();
+/// custom.id = "MyWebMercatorQuad".to_string();
+/// register_global(vec![custom], false).unwrap();
+/// assert!(tms().lookup("MyWebMercatorQuad").is_ok());
+///
+/// remove_global("MyWebMercatorQuad").unwrap();
+/// assert!(tms().lookup("MyWebMercatorQuad").is_err());
+/// ```
+pub fn register_global(
+    custom_tms: Vec<TileMatrixSet>,
+    overwrite: bool,
+) -> Result<(), RegistryError> {
+    update_global(|sets| sets.register(custom_tms, overwrite))
+}
+
+/// Remove a TileMatrixSet from the global registry.
+pub fn remove_global(id: &str) -> Result<TileMatrixSet, RegistryError> {
+    update_global(|sets| sets.remove(id))
+}
+
+/// Apply a change to a copy of the global registry and replace the registry on success.
+fn update_global<T>(
+    update: impl FnOnce(&mut TileMatrixSets) -> Result<T, RegistryError>,
+) -> Result<T, RegistryError> {
+    let mut global = global_tms().write().unwrap_or_else(PoisonError::into_inner);
+    let mut sets = TileMatrixSets::clone(&global);
+    let result = update(&mut sets)?;
+    *global = Arc::new(sets);
+    Ok(result)
+}
+
+/// Errors of TileMatrixSets skipped when loading `TILEMATRIXSET_DIRECTORY` into the global
+/// registry, together with the file or directory path.
+pub fn tms_load_errors() -> &'static [(PathBuf, RegistryError)] {
+    global_tms();
+    LOAD_ERRORS.get().map_or(&[], Vec::as_slice)
+}
+
+static LOAD_ERRORS: OnceCell<Vec<(PathBuf, RegistryError)>> = OnceCell::new();
+
+/// Current snapshot of the global registry, replaced on changes
+fn global_tms() -> &'static RwLock<Arc<TileMatrixSets>> {
+    static TMS: OnceCell<RwLock<Arc<TileMatrixSets>>> = OnceCell::new();
     TMS.get_or_init(|| {
         let mut sets = TileMatrixSets::new();
         let tms = vec![
             #[cfg(feature = "projtransform")]
             include_str!("../data/CanadianNAD83_LCC.json"),
-            //include_str!("../data/CDB1GlobalGrid.json"), // Error("missing field `coalesc`", line: 19, column: 67)
+            include_str!("../data/CDB1GlobalGrid.json"),
             #[cfg(feature = "projtransform")]
             include_str!("../data/EuropeanETRS89_LAEAQuad.json"),
-            //include_str!("../data/GNOSISGlobalGrid.json"), // Error("missing field `coalesc`", line: 31, column: 66)
-            #[cfg(feature = "projtransform")]
+            include_str!("../data/GNOSISGlobalGrid.json"),
             include_str!("../data/UPSAntarcticWGS84Quad.json"),
-            #[cfg(feature = "projtransform")]
             include_str!("../data/UPSArcticWGS84Quad.json"),
-            #[cfg(feature = "projtransform")]
             include_str!("../data/UTM31WGS84Quad.json"),
             include_str!("../data/WebMercatorQuad.json"),
             include_str!("../data/WGS1984Quad.json"),
@@ -96,9 +202,13 @@ pub fn tms() -> &'static TileMatrixSets {
         .map(|data| TileMatrixSet::from_json(data).unwrap())
         .collect::<Vec<_>>();
         sets.register(tms, false).unwrap();
-        // user_tms_dir = os.environ.get("TILEMATRIXSET_DIRECTORY", None)
-        // if user_tms_dir:
-        //     tms_paths.extend(list(pathlib.Path(user_tms_dir).glob("*.json")))
-        sets
+        let errors = match std::env::var_os("TILEMATRIXSET_DIRECTORY") {
+            Some(user_tms_dir) => sets
+                .register_dir(&user_tms_dir, true)
+                .unwrap_or_else(|e| vec![(user_tms_dir.into(), e)]),
+            None => Vec::new(),
+        };
+        let _ = LOAD_ERRORS.set(errors);
+        RwLock::new(Arc::new(sets))
     })
 }
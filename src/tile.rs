@@ -1,7 +1,12 @@
+use crate::tms::TmsError;
+use serde::{Deserialize, Serialize};
 use serde_json::json;
+use std::cmp::Ordering;
+use std::fmt;
+use std::str::FromStr;
 
 /// A xmin,ymin,xmax,ymax coordinates tuple.
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
 pub struct BoundingBox {
     /// min horizontal coordinate.
     pub left: f64,
@@ -23,10 +28,92 @@ impl BoundingBox {
             top,
         }
     }
+
+    /// Horizontal extent.
+    pub fn width(&self) -> f64 {
+        self.right - self.left
+    }
+
+    /// Vertical extent.
+    pub fn height(&self) -> f64 {
+        self.top - self.bottom
+    }
+
+    /// Center point.
+    pub fn center(&self) -> Coords {
+        Coords::new(
+            (self.left + self.right) / 2.0,
+            (self.bottom + self.top) / 2.0,
+        )
+    }
+
+    /// Check if a point is inside the bounding box or on its boundary.
+    pub fn contains(&self, point: &Coords) -> bool {
+        point.x >= self.left
+            && point.x <= self.right
+            && point.y >= self.bottom
+            && point.y <= self.top
+    }
+
+    /// Check if the bounding boxes overlap with a non-zero area.
+    pub fn intersects(&self, other: &BoundingBox) -> bool {
+        self.left < other.right
+            && self.right > other.left
+            && self.top > other.bottom
+            && self.bottom < other.top
+    }
+
+    /// Common area of both bounding boxes.
+    ///
+    /// Returns `None` if the bounding boxes don't overlap.
+    pub fn intersection(&self, other: &BoundingBox) -> Option<BoundingBox> {
+        if !self.intersects(other) {
+            return None;
+        }
+        Some(BoundingBox::new(
+            self.left.max(other.left),
+            self.bottom.max(other.bottom),
+            self.right.min(other.right),
+            self.top.min(other.top),
+        ))
+    }
+
+    /// Smallest bounding box containing both bounding boxes.
+    pub fn union(&self, other: &BoundingBox) -> BoundingBox {
+        BoundingBox::new(
+            self.left.min(other.left),
+            self.bottom.min(other.bottom),
+            self.right.max(other.right),
+            self.top.max(other.top),
+        )
+    }
+
+    /// Bounding box expanded by `distance` on each side.
+    ///
+    /// A negative distance shrinks the bounding box.
+    pub fn buffer(&self, distance: f64) -> BoundingBox {
+        BoundingBox::new(
+            self.left - distance,
+            self.bottom - distance,
+            self.right + distance,
+            self.top + distance,
+        )
+    }
+}
+
+impl fmt::Display for BoundingBox {
+    /// Format as `left,bottom,right,top`.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{},{},{},{}",
+            self.left, self.bottom, self.right, self.top
+        )
+    }
 }
 
 /// A x,y Coordinates pair.
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
 pub struct Coords {
     /// horizontal coordinate input projection unit.
     pub x: f64,
@@ -42,7 +129,9 @@ impl Coords {
 }
 
 /// TileMatrixSet X,Y,Z tile indices.
-#[derive(Debug, Clone, PartialEq)]
+///
+/// Tiles are ordered by zoom level, column and row.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub struct Xyz {
     /// horizontal index.
     pub x: u64,
@@ -59,6 +148,44 @@ impl Xyz {
     }
 }
 
+impl Ord for Xyz {
+    fn cmp(&self, other: &Self) -> Ordering {
+        (self.z, self.x, self.y).cmp(&(other.z, other.x, other.y))
+    }
+}
+
+impl PartialOrd for Xyz {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl fmt::Display for Xyz {
+    /// Format as `z/x/y`.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}/{}/{}", self.z, self.x, self.y)
+    }
+}
+
+impl FromStr for Xyz {
+    type Err = TmsError;
+
+    /// Parse `z/x/y`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut parts = s.split('/');
+        let mut next = || parts.next().map(str::trim);
+        let (Some(z), Some(x), Some(y), None) = (next(), next(), next(), next()) else {
+            return Err(TmsError::TileArgParsingError(s.to_string()));
+        };
+        let parse_error = |_| TmsError::TileArgParsingError(s.to_string());
+        Ok(Xyz::new(
+            x.parse().map_err(parse_error)?,
+            y.parse().map_err(parse_error)?,
+            z.parse().map_err(parse_error)?,
+        ))
+    }
+}
+
 /// Create a GeoJSON feature from a bbox.
 pub fn bbox_to_feature(west: f64, south: f64, east: f64, north: f64) -> serde_json::Value {
     json!({
@@ -1,3 +1,4 @@
+use crate::crs::crs_info;
 use ogcapi_types::tiles::{OrderedAxes, TileMatrixSet};
 use std::path::PathBuf;
 
@@ -7,11 +8,17 @@ pub enum TileMatrixSetError {
     JsonError(#[from] serde_json::Error),
     #[error("{0}: {1}")]
     FileError(PathBuf, #[source] std::io::Error),
+    #[error("Invalid XML: {0}")]
+    XmlError(String),
+    #[error("Invalid WMTS capabilities: {0}")]
+    CapabilitiesError(String),
 }
 
 pub trait TileMatrixSetOps: Sized {
     fn from_json_file(json_path: &str) -> Result<Self, TileMatrixSetError>;
     fn from_json(json: &str) -> Result<Self, TileMatrixSetError>;
+    /// Serialize with OGC `variableMatrixWidths` keys.
+    fn to_json(&self) -> Result<String, TileMatrixSetError>;
     /// Check if CRS has inverted AXIS (lat,lon) instead of (lon,lat).
     fn crs_axis_inverted(&self) -> bool;
 }
@@ -23,14 +30,52 @@ impl TileMatrixSetOps for TileMatrixSet {
         TileMatrixSet::from_json(&content)
     }
     fn from_json(json: &str) -> Result<Self, TileMatrixSetError> {
-        serde_json::from_str(json).map_err(Into::into)
+        let mut value: serde_json::Value = serde_json::from_str(json)?;
+        rename_variable_matrix_widths_keys(&mut value, OGC_TO_OGCAPI_TYPES);
+        serde_json::from_value(value).map_err(Into::into)
+    }
+    fn to_json(&self) -> Result<String, TileMatrixSetError> {
+        let mut value = serde_json::to_value(self)?;
+        rename_variable_matrix_widths_keys(&mut value, OGCAPI_TYPES_TO_OGC);
+        serde_json::to_string(&value).map_err(Into::into)
     }
     /// Check if CRS has inverted AXIS (lat,lon) instead of (lon,lat).
     fn crs_axis_inverted(&self) -> bool {
         if let Some(axes) = &self.ordered_axes {
             ordered_axes_inverted(axes)
         } else {
-            false // TODO: Check CRS axis ordering
+            crs_info(&self.crs).map_or(false, |info| info.axis_inverted)
+        }
+    }
+}
+
+/// OGC `variableMatrixWidths` keys and the field names used by ogcapi-types.
+//
+// ogcapi-types (de)serializes `coalesc` and `smaxTileRow` instead of `coalesce` and `maxTileRow`.
+const OGC_TO_OGCAPI_TYPES: [(&str, &str); 2] =
+    [("coalesce", "coalesc"), ("maxTileRow", "smaxTileRow")];
+const OGCAPI_TYPES_TO_OGC: [(&str, &str); 2] =
+    [("coalesc", "coalesce"), ("smaxTileRow", "maxTileRow")];
+
+/// Rename `variableMatrixWidths` keys of a TileMatrixSet JSON value.
+fn rename_variable_matrix_widths_keys(tms: &mut serde_json::Value, keys: [(&str, &str); 2]) {
+    let Some(matrices) = tms
+        .get_mut("tileMatrices")
+        .and_then(|matrices| matrices.as_array_mut())
+    else {
+        return;
+    };
+    for widths in matrices
+        .iter_mut()
+        .filter_map(|matrix| matrix.get_mut("variableMatrixWidths"))
+        .filter_map(|widths| widths.as_array_mut())
+    {
+        for width in widths.iter_mut().filter_map(|width| width.as_object_mut()) {
+            for (from, to) in keys {
+                if let Some(value) = width.remove(from) {
+                    width.insert(to.to_string(), value);
+                }
+            }
         }
     }
 }
@@ -53,4 +98,34 @@ mod test {
         let tms = TileMatrixSet::from_json_file("./data/WebMercatorQuad.json").unwrap();
         println!("{}", serde_json::to_string_pretty(&tms).unwrap());
     }
+
+    #[test]
+    fn parse_variable_matrix_widths() {
+        let tms = TileMatrixSet::from_json_file("./data/GNOSISGlobalGrid.json").unwrap();
+        let widths = tms.tile_matrices[2]
+            .variable_matrix_widths
+            .as_ref()
+            .unwrap();
+        assert_eq!(widths.len(), 4);
+        assert_eq!(u64::from(widths[0].coalesc), 4);
+        assert_eq!(widths[3].min_tile_row, 7);
+        assert_eq!(widths[3].smax_tile_row, 7);
+    }
+
+    #[test]
+    fn serialize_variable_matrix_widths() {
+        let tms = TileMatrixSet::from_json_file("./data/GNOSISGlobalGrid.json").unwrap();
+        let json = tms.to_json().unwrap();
+        let value: serde_json::Value = serde_json::from_str(&json).unwrap();
+        let width = &value["tileMatrices"][2]["variableMatrixWidths"][3];
+        assert_eq!(width["coalesce"], 4);
+        assert_eq!(width["maxTileRow"], 7);
+        assert!(width.get("coalesc").is_none());
+        assert!(width.get("smaxTileRow").is_none());
+        let parsed = TileMatrixSet::from_json(&json).unwrap();
+        assert_eq!(
+            serde_json::to_value(parsed).unwrap(),
+            serde_json::to_value(tms).unwrap()
+        );
+    }
 }
@@ -0,0 +1,296 @@
+use crate::tile::Xyz;
+use crate::tms::{Result, Tms, TmsError};
+use crate::tms_iterator::XyzIterator;
+use ogcapi_types::tiles::{TileMatrixLimits, VariableMatrixWidth};
+
+/// Range of tiles within a tile matrix.
+///
+/// Limits are inclusive. Coalesced tiles are identified by their leftmost tile index.
+#[derive(Clone, Debug)]
+pub struct TileRange {
+    pub(crate) z: u8,
+    pub(crate) x_min: u64,
+    pub(crate) x_max: u64,
+    pub(crate) y_min: u64,
+    pub(crate) y_max: u64,
+    /// Coalesced rows of the tile matrix
+    pub(crate) variable_matrix_widths: Vec<VariableMatrixWidth>,
+}
+
+// `VariableMatrixWidth` doesn't implement `PartialEq`
+impl PartialEq for TileRange {
+    fn eq(&self, other: &Self) -> bool {
+        (self.z, self.x_min, self.x_max, self.y_min, self.y_max)
+            == (other.z, other.x_min, other.x_max, other.y_min, other.y_max)
+            && self.same_matrix_widths(other)
+    }
+}
+
+impl Eq for TileRange {}
+
+impl TileRange {
+    /// Create a tile range within a tile matrix of `tms`.
+    ///
+    /// Columns of coalesced rows have to start and end at coalesced tile boundaries.
+    pub fn new(z: u8, x_min: u64, x_max: u64, y_min: u64, y_max: u64, tms: &Tms) -> Result<Self> {
+        if x_min > x_max || y_min > y_max {
+            return Err(TmsError::InvalidTileRange(format!(
+                "minimum greater than maximum in cols {x_min}-{x_max}, rows {y_min}-{y_max}"
+            )));
+        }
+        let matrix = tms.matrix_range(z)?;
+        if x_max > matrix.x_max || y_max > matrix.y_max {
+            return Err(TmsError::InvalidTileRange(format!(
+                "cols {x_min}-{x_max}, rows {y_min}-{y_max} outside of tile matrix `{}`",
+                tms.matrix(z)?.as_ref().id
+            )));
+        }
+        let range = TileRange {
+            z,
+            x_min,
+            x_max,
+            y_min,
+            y_max,
+            variable_matrix_widths: matrix.variable_matrix_widths,
+        };
+        for width in range.coalesced_widths() {
+            let coalesce = u64::from(width.coalesc);
+            if !range.is_aligned(coalesce) {
+                return Err(TmsError::InvalidTileRange(format!(
+                    "cols {x_min}-{x_max} not aligned to {coalesce} coalesced tiles in rows {}-{}",
+                    width.min_tile_row, width.smax_tile_row
+                )));
+            }
+        }
+        Ok(range)
+    }
+
+    /// Tile range with columns extended to the boundaries of coalesced tiles.
+    ///
+    /// The range is validated against the tile matrix of `tms`.
+    pub(crate) fn aligned(mut self, tms: &Tms) -> Result<Self> {
+        self.align();
+        TileRange::new(self.z, self.x_min, self.x_max, self.y_min, self.y_max, tms)
+    }
+
+    /// Extend the columns to the boundaries of coalesced tiles of all rows.
+    fn align(&mut self) {
+        let coalescences = self
+            .coalesced_widths()
+            .map(|width| u64::from(width.coalesc))
+            .collect::<Vec<_>>();
+        if coalescences
+            .iter()
+            .all(|coalesce| self.is_aligned(*coalesce))
+        {
+            return;
+        }
+        let block = coalescences.into_iter().fold(1, lcm);
+        self.x_min = self.x_min / block * block;
+        self.x_max = (self.x_max / block + 1) * block - 1;
+    }
+
+    /// Coalesced rows within the range.
+    fn coalesced_widths(&self) -> impl Iterator<Item = &VariableMatrixWidth> {
+        self.variable_matrix_widths
+            .iter()
+            .filter(|width| width.min_tile_row <= self.y_max && self.y_min <= width.smax_tile_row)
+    }
+
+    /// Check if the columns start and end at the boundaries of coalesced tiles.
+    fn is_aligned(&self, coalesce: u64) -> bool {
+        self.x_min % coalesce == 0
+            && (self.x_max % coalesce == 0 || (self.x_max + 1) % coalesce == 0)
+    }
+
+    /// Check if both ranges have the same coalesced rows.
+    fn same_matrix_widths(&self, other: &TileRange) -> bool {
+        let widths = |range: &TileRange| {
+            range
+                .variable_matrix_widths
+                .iter()
+                .map(|width| (width.coalesc, width.min_tile_row, width.smax_tile_row))
+                .collect::<Vec<_>>()
+        };
+        widths(self) == widths(other)
+    }
+
+    /// Tile range of OGC TileMatrixLimits.
+    ///
+    /// The limits are validated against the tile matrix of `tms`.
+    pub fn from_tile_matrix_limits(limits: &TileMatrixLimits, tms: &Tms) -> Result<Self> {
+        let z = tms
+            .zoom_by_id(&limits.tile_matrix)
+            .ok_or_else(|| TmsError::InvalidZoomId(limits.tile_matrix.clone()))?;
+        TileRange::new(
+            z,
+            limits.min_tile_col,
+            limits.max_tile_col,
+            limits.min_tile_row,
+            limits.max_tile_row,
+            tms,
+        )
+    }
+
+    /// OGC TileMatrixLimits of this range.
+    pub fn to_tile_matrix_limits(&self, tms: &Tms) -> Result<TileMatrixLimits> {
+        Ok(TileMatrixLimits {
+            tile_matrix: tms.matrix(self.z)?.as_ref().id.clone(),
+            min_tile_row: self.y_min,
+            max_tile_row: self.y_max,
+            min_tile_col: self.x_min,
+            max_tile_col: self.x_max,
+        })
+    }
+
+    /// Zoom level
+    pub fn z(&self) -> u8 {
+        self.z
+    }
+
+    /// Minimum column
+    pub fn x_min(&self) -> u64 {
+        self.x_min
+    }
+
+    /// Maximum column
+    pub fn x_max(&self) -> u64 {
+        self.x_max
+    }
+
+    /// Minimum row
+    pub fn y_min(&self) -> u64 {
+        self.y_min
+    }
+
+    /// Maximum row
+    pub fn y_max(&self) -> u64 {
+        self.y_max
+    }
+
+    /// Coalesced rows of the tile matrix
+    pub fn variable_matrix_widths(&self) -> &[VariableMatrixWidth] {
+        &self.variable_matrix_widths
+    }
+
+    /// Number of coalesced tiles in a row.
+    pub fn coalescence(&self, row: u64) -> u64 {
+        coalescence(&self.variable_matrix_widths, row)
+    }
+
+    /// Tile visited at column `x` and row `y`.
+    ///
+    /// Coalesced tiles are returned with their leftmost index, when visiting their first column
+    /// within the limits.
+    pub fn visit(&self, x: u64, y: u64) -> Option<Xyz> {
+        let coalesce = self.coalescence(y);
+        let leftmost = x / coalesce * coalesce;
+        if x == leftmost || x == self.x_min {
+            Some(Xyz::new(leftmost, y, self.z))
+        } else {
+            None
+        }
+    }
+
+    /// Tiles within limits, column by column.
+    pub fn tiles(&self) -> impl Iterator<Item = Xyz> + '_ {
+        (self.x_min..=self.x_max)
+            .flat_map(move |x| (self.y_min..=self.y_max).filter_map(move |y| self.visit(x, y)))
+    }
+
+    /// Check if a tile is within the range.
+    pub fn contains(&self, tile: &Xyz) -> bool {
+        if tile.z != self.z || tile.y < self.y_min || tile.y > self.y_max {
+            return false;
+        }
+        let coalesce = self.coalescence(tile.y);
+        tile.x % coalesce == 0
+            && tile.x <= self.x_max
+            && tile.x.saturating_add(coalesce - 1) >= self.x_min
+    }
+
+    /// Number of tiles within the range.
+    pub fn count(&self) -> u64 {
+        let cols = |coalesce: u64| self.x_max / coalesce - self.x_min / coalesce + 1;
+        let mut rows = self.y_max - self.y_min + 1;
+        let mut count = 0;
+        for width in &self.variable_matrix_widths {
+            let min_row = width.min_tile_row.max(self.y_min);
+            let max_row = width.smax_tile_row.min(self.y_max);
+            if min_row <= max_row {
+                rows = rows.saturating_sub(max_row - min_row + 1);
+                count += (max_row - min_row + 1) * cols(width.coalesc.into());
+            }
+        }
+        count + rows * cols(1)
+    }
+
+    /// Tiles contained in both ranges.
+    ///
+    /// Returns `None` for ranges of different tile matrices or without common tiles.
+    pub fn intersection(&self, other: &TileRange) -> Option<TileRange> {
+        if self.z != other.z || !self.same_matrix_widths(other) {
+            return None;
+        }
+        let mut range = TileRange {
+            z: self.z,
+            x_min: self.x_min.max(other.x_min),
+            x_max: self.x_max.min(other.x_max),
+            y_min: self.y_min.max(other.y_min),
+            y_max: self.y_max.min(other.y_max),
+            variable_matrix_widths: self.variable_matrix_widths.clone(),
+        };
+        if range.x_min > range.x_max || range.y_min > range.y_max {
+            return None;
+        }
+        range.align();
+        Some(range)
+    }
+
+    /// Smallest range containing both ranges.
+    ///
+    /// Columns are extended to the boundaries of coalesced tiles.
+    /// Returns `None` for ranges of different tile matrices.
+    pub fn union(&self, other: &TileRange) -> Option<TileRange> {
+        if self.z != other.z || !self.same_matrix_widths(other) {
+            return None;
+        }
+        let mut range = TileRange {
+            z: self.z,
+            x_min: self.x_min.min(other.x_min),
+            x_max: self.x_max.max(other.x_max),
+            y_min: self.y_min.min(other.y_min),
+            y_max: self.y_max.max(other.y_max),
+            variable_matrix_widths: self.variable_matrix_widths.clone(),
+        };
+        range.align();
+        Some(range)
+    }
+}
+
+impl IntoIterator for TileRange {
+    type Item = Xyz;
+    type IntoIter = XyzIterator;
+
+    fn into_iter(self) -> Self::IntoIter {
+        XyzIterator::new(vec![self])
+    }
+}
+
+/// Least common multiple
+fn lcm(a: u64, b: u64) -> u64 {
+    let (mut x, mut y) = (a, b);
+    while y > 0 {
+        (x, y) = (y, x % y);
+    }
+    a / x * b
+}
+
+/// Number of tiles coalescing in a single tile for a row.
+pub(crate) fn coalescence(variable_matrix_widths: &[VariableMatrixWidth], row: u64) -> u64 {
+    variable_matrix_widths
+        .iter()
+        .find(|width| width.min_tile_row <= row && row <= width.smax_tile_row)
+        .map(|width| u64::from(width.coalesc))
+        .unwrap_or(1)
+}
@@ -0,0 +1,255 @@
+use crate::tile::{BoundingBox, Coords};
+use crate::tms::{bottom_left_origin, Result, Tms, TmsError};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// TileJSON version written by [`TileJsonBuilder`].
+pub const TILEJSON_VERSION: &str = "3.0.0";
+
+/// TileJSON 3.0.0 document.
+///
+/// See <https://github.com/mapbox/tilejson-spec/tree/master/3.0.0>
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct TileJson {
+    pub tilejson: String,
+    pub tiles: Vec<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub vector_layers: Vec<VectorLayer>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub attribution: Option<String>,
+    /// Geographic bounds as `[west, south, east, north]`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub bounds: Option<[f64; 4]>,
+    /// Default position as `[longitude, latitude, zoom]`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub center: Option<[f64; 3]>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub maxzoom: Option<u8>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub minzoom: Option<u8>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    /// Tile row order, `xyz` (rows from the top) or `tms` (rows from the bottom)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub scheme: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub version: Option<String>,
+}
+
+/// Vector tile layer description.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct VectorLayer {
+    pub id: String,
+    /// Attribute names and their type description
+    pub fields: BTreeMap<String, String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub maxzoom: Option<u8>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub minzoom: Option<u8>,
+}
+
+impl VectorLayer {
+    /// Create a layer description without fields.
+    pub fn new(id: &str) -> Self {
+        VectorLayer {
+            id: id.to_string(),
+            fields: BTreeMap::new(),
+            description: None,
+            maxzoom: None,
+            minzoom: None,
+        }
+    }
+}
+
+/// Builder for a [`TileJson`] document of a tile matrix set.
+///
+/// ```rust
+/// use tile_grid::{tms, BoundingBox, TileJsonBuilder};
+///
+/// let tms = tms().lookup("WebMercatorQuad").unwrap();
+/// let tilejson = TileJsonBuilder::new(&tms)
+///     .tiles(["https://example.com/tiles/{z}/{x}/{y}.pbf"])
+///     .extent(BoundingBox::new(5.9, 45.8, 10.5, 47.8))
+///     .zoom_range(6, 14)
+///     .build()
+///     .unwrap();
+/// assert_eq!(tilejson.bounds, Some([5.9, 45.8, 10.5, 47.8]));
+/// assert_eq!(tilejson.center, Some([8.2, 46.8, 6.0]));
+/// ```
+#[derive(Clone, Debug)]
+pub struct TileJsonBuilder<'a> {
+    tms: &'a Tms,
+    tiles: Vec<String>,
+    extent: Option<BoundingBox>,
+    minzoom: Option<u8>,
+    maxzoom: Option<u8>,
+    center: Option<(Coords, u8)>,
+    vector_layers: Vec<VectorLayer>,
+    name: Option<String>,
+    description: Option<String>,
+    attribution: Option<String>,
+    version: Option<String>,
+}
+
+impl<'a> TileJsonBuilder<'a> {
+    pub fn new(tms: &'a Tms) -> Self {
+        TileJsonBuilder {
+            tms,
+            tiles: Vec::new(),
+            extent: None,
+            minzoom: None,
+            maxzoom: None,
+            center: None,
+            vector_layers: Vec::new(),
+            name: None,
+            description: None,
+            attribution: None,
+            version: None,
+        }
+    }
+
+    /// Tile URL templates with `{z}`, `{x}` and `{y}` or `{-y}` placeholders.
+    ///
+    /// Other placeholders like `{s}` are written unchanged.
+    pub fn tiles<I, S>(mut self, tiles: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.tiles.extend(tiles.into_iter().map(Into::into));
+        self
+    }
+
+    /// Geographic extent of the data. Extents crossing the antimeridian have `left > right`.
+    ///
+    /// Defaults to the bounds of the tile matrix set.
+    pub fn extent(mut self, extent: BoundingBox) -> Self {
+        self.extent = Some(extent);
+        self
+    }
+
+    /// Zoom levels with data. Defaults to all levels of the tile matrix set.
+    pub fn zoom_range(mut self, minzoom: u8, maxzoom: u8) -> Self {
+        self.minzoom = Some(minzoom);
+        self.maxzoom = Some(maxzoom);
+        self
+    }
+
+    /// Default position. Defaults to the center of the bounds at `minzoom`.
+    pub fn center(mut self, center: Coords, zoom: u8) -> Self {
+        self.center = Some((center, zoom));
+        self
+    }
+
+    /// Vector tile layers, written unchanged.
+    pub fn vector_layers(mut self, vector_layers: Vec<VectorLayer>) -> Self {
+        self.vector_layers = vector_layers;
+        self
+    }
+
+    pub fn name(mut self, name: &str) -> Self {
+        self.name = Some(name.to_string());
+        self
+    }
+
+    pub fn description(mut self, description: &str) -> Self {
+        self.description = Some(description.to_string());
+        self
+    }
+
+    pub fn attribution(mut self, attribution: &str) -> Self {
+        self.attribution = Some(attribution.to_string());
+        self
+    }
+
+    pub fn version(mut self, version: &str) -> Self {
+        self.version = Some(version.to_string());
+        self
+    }
+
+    /// Build the TileJSON document.
+    ///
+    /// Bounds are clipped to the geographic bounds of the tile matrix set.
+    /// Tile matrix sets with a bottom left origin use the `tms` scheme.
+    pub fn build(self) -> Result<TileJson> {
+        if self.tiles.is_empty() {
+            return Err(TmsError::UrlTemplateError(
+                "TileJSON requires at least one tile URL".to_string(),
+            ));
+        }
+        for template in &self.tiles {
+            check_tile_url(template)?;
+        }
+        let minzoom = self.minzoom.unwrap_or_else(|| self.tms.minzoom());
+        let maxzoom = self.maxzoom.unwrap_or_else(|| self.tms.maxzoom());
+        if minzoom > maxzoom {
+            return Err(TmsError::InvalidZoom(minzoom));
+        }
+        let tms_bounds = self.tms.bbox()?;
+        let bounds = match self.extent {
+            Some(extent) => clamp_bounds(&extent, &tms_bounds),
+            None => tms_bounds,
+        };
+        let (center, center_zoom) = self
+            .center
+            .unwrap_or_else(|| (bounds_center(&bounds), minzoom));
+        let scheme = if bottom_left_origin(self.tms.matrix(minzoom)?.as_ref()) {
+            "tms"
+        } else {
+            "xyz"
+        };
+        Ok(TileJson {
+            tilejson: TILEJSON_VERSION.to_string(),
+            tiles: self.tiles,
+            vector_layers: self.vector_layers,
+            attribution: self.attribution,
+            bounds: Some([bounds.left, bounds.bottom, bounds.right, bounds.top]),
+            center: Some([center.x, center.y, f64::from(center_zoom)]),
+            description: self.description,
+            maxzoom: Some(maxzoom),
+            minzoom: Some(minzoom),
+            name: self.name,
+            scheme: Some(scheme.to_string()),
+            version: self.version,
+        })
+    }
+}
+
+/// Check if a tile URL template contains the tile index placeholders.
+fn check_tile_url(template: &str) -> Result<()> {
+    let has_row = template.contains("{y}") || template.contains("{-y}");
+    if !template.contains("{z}") || !template.contains("{x}") || !has_row {
+        return Err(TmsError::UrlTemplateError(format!(
+            "missing `{{z}}`, `{{x}}` or `{{y}}` placeholder in `{template}`"
+        )));
+    }
+    Ok(())
+}
+
+/// Clamp each coordinate of an extent into the bounds, keeping extents crossing the antimeridian.
+fn clamp_bounds(extent: &BoundingBox, bounds: &BoundingBox) -> BoundingBox {
+    let clamp_x = |x: f64| x.max(bounds.left).min(bounds.right);
+    let clamp_y = |y: f64| y.max(bounds.bottom).min(bounds.top);
+    BoundingBox::new(
+        clamp_x(extent.left),
+        clamp_y(extent.bottom),
+        clamp_x(extent.right),
+        clamp_y(extent.top),
+    )
+}
+
+/// Center of geographic bounds, which may cross the antimeridian.
+fn bounds_center(bounds: &BoundingBox) -> Coords {
+    let mut center = bounds.center();
+    if bounds.left > bounds.right {
+        center.x += 180.0;
+        if center.x > 180.0 {
+            center.x -= 360.0;
+        }
+    }
+    center
+}
@@ -0,0 +1,78 @@
+use crate::tile::BoundingBox;
+use crate::tile_range::TileRange;
+use crate::tms::{Result, Tms};
+use ogcapi_types::tiles::{BoundingBox2D, DataType, TileSet, TitleDescriptionKeywords};
+
+impl Tms {
+    /// OGC API - Tiles TileSet metadata for a data extent and zoom range.
+    ///
+    /// # Arguments
+    /// * `data_type` - Type of data represented in the tileset.
+    /// * `extent` - Geographic bounding box of the data. Extents crossing the antimeridian have `left > right`.
+    /// * `minzoom` - Minimum zoom level.
+    /// * `maxzoom` - Maximum zoom level.
+    ///
+    /// The tileset contains one `tileMatrixSetLimits` entry per zoom level.
+    /// Links are empty and have to be added by the caller.
+    pub fn tileset(
+        &self,
+        data_type: DataType,
+        extent: &BoundingBox,
+        minzoom: u8,
+        maxzoom: u8,
+    ) -> Result<TileSet> {
+        let ranges = self.merged_tile_limits(extent, minzoom, maxzoom)?;
+        let tile_matrix_set_limits = ranges
+            .iter()
+            .map(|range| range.to_tile_matrix_limits(self))
+            .collect::<Result<Vec<_>>>()?;
+        Ok(TileSet {
+            title_description_keywords: TitleDescriptionKeywords {
+                title: None,
+                description: None,
+                keywords: None,
+            },
+            data_type,
+            tile_matrix_set_uri: self.tms.uri.clone(),
+            tile_matrix_set_limits: Some(tile_matrix_set_limits),
+            crs: self.crs().clone(),
+            epoch: None,
+            links: Vec::new(),
+            layers: None,
+            bounding_box: Some(BoundingBox2D {
+                lower_left: [extent.left, extent.bottom],
+                upper_right: [extent.right, extent.top],
+                crs: Some(self.geographic_crs().clone()),
+                ordered_axes: None,
+            }),
+            style: None,
+            center_point: None,
+            license: None,
+            access_constraints: None,
+            version: None,
+            created: None,
+            updated: None,
+            point_of_contact: None,
+            media_types: None,
+        })
+    }
+
+    /// Tile ranges overlapped by a geographic extent, with one range per zoom level.
+    pub(crate) fn merged_tile_limits(
+        &self,
+        extent: &BoundingBox,
+        minzoom: u8,
+        maxzoom: u8,
+    ) -> Result<Vec<TileRange>> {
+        let limits = self.extent_limits(extent, minzoom, maxzoom, false)?;
+        let mut ranges: Vec<TileRange> = Vec::new();
+        for limit in limits {
+            // Merge the parts of an extent crossing the antimeridian
+            match ranges.last_mut() {
+                Some(last) if last.z == limit.z => *last = last.union(&limit).unwrap(),
+                _ => ranges.push(limit),
+            }
+        }
+        Ok(ranges)
+    }
+}
@@ -1,18 +1,23 @@
+use crate::crs::crs_info;
+use crate::curve::Curve;
 use crate::quadkey::check_quadkey_support;
-use crate::tile::{BoundingBox, Coords, Xyz};
+use crate::tile::{bbox_to_feature, BoundingBox, Coords, Xyz};
 use crate::tile_matrix_set::{ordered_axes_inverted, TileMatrixSetOps};
-use crate::tms_iterator::XyzIterator;
-use crate::transform::{merc_tile_ul, Error::TransformationUnsupported, Transform, Transformer};
-use ogcapi_types::common::Crs;
+use crate::tile_range::{coalescence, TileRange};
+use crate::tms_iterator::{CurveIterator, TileVisit, XyzIterator};
+use crate::transform::{
+    merc_tile_ul, Error::TransformationUnsupported, SharedTransformer as Transformer, Transform,
+};
+use ogcapi_types::common::{Authority, Crs};
 use ogcapi_types::tiles::{
     BoundingBox2D, CornerOfOrigin, OrderedAxes, TileMatrix, TileMatrixSet, TitleDescriptionKeywords,
 };
+use serde_json::json;
 use std::convert::AsRef;
-use std::f64::consts::PI;
 use std::num::{NonZeroU16, NonZeroU64};
 
 /// Tile Matrix Set API.
-#[derive(Debug)]
+#[derive(Clone, Debug)]
 pub struct Tms {
     pub tms: TileMatrixSet,
     pub is_quadtree: bool,
@@ -21,6 +26,14 @@ pub struct Tms {
     geographic_crs: Crs, // default=WGS84_CRS
     to_geographic: Option<Transformer>,
     from_geographic: Option<Transformer>,
+    // TMS bounding box in TileMatrixSet's CRS
+    xy_bbox: BoundingBox,
+    // CRS units in meters
+    meters_per_unit: f64,
+    // CRS axis order is (lat,lon)
+    axis_inverted: bool,
+    // Zoom level of each matrix in `tms.tile_matrices`
+    zooms: Vec<u8>,
 }
 
 #[derive(thiserror::Error, Debug)]
@@ -35,27 +48,36 @@ pub enum TmsError {
     TransformationError(#[from] crate::transform::Error),
     #[error("Zero width or height")]
     NonZeroError,
+    #[error("TileMatrixSet without tile matrices")]
+    NoTileMatrices,
     // #[error("Raised when math errors occur beyond ~85 degrees N or S")]
     // InvalidLatitudeError,
-    // #[error("TileMatrix not found for level: {0} - Unable to construct tileMatrix for TMS with variable scale")]
-    // InvalidZoomError(u8),
-    // #[error("Raised when errors occur in parsing a function's tile arg(s)")]
-    // TileArgParsingError,
-    // #[error("Raised when a custom TileMatrixSet doesn't support quadkeys")]
-    // NoQuadkeySupport,
-    // #[error("Raised when errors occur in computing or parsing quad keys")]
-    // QuadKeyError,
+    #[error("TileMatrix not found for level: {0}")]
+    InvalidZoomError(u8),
+    #[error("Invalid tile `{0}`, expected `z/x/y`")]
+    TileArgParsingError(String),
+    #[error("TileMatrix not found for level: {0} - Unable to construct tileMatrix for TMS with variable scale")]
+    VariableScale(u8),
+    #[error("TileMatrixSet can't be represented as Grid: {0}")]
+    GridConversionError(String),
+    #[error("TileMatrixSet can't be represented in WMTS capabilities: {0}")]
+    CapabilitiesError(String),
+    #[error("Unknown units of CRS `{0}`")]
+    UnknownCrsUnits(String),
+    #[error("URL template error: {0}")]
+    UrlTemplateError(String),
+    #[error("Invalid tile range: {0}")]
+    InvalidTileRange(String),
+    #[error("This Tile Matrix Set doesn't support 2 x 2 quadkeys")]
+    NoQuadkeySupport,
+    #[error("Invalid quadkey: {0}")]
+    QuadKeyError(String),
+    #[error("PMTiles tile ID error: {0}")]
+    PmTilesError(String),
 }
 
 pub type Result<T> = std::result::Result<T, TmsError>;
 
-impl Clone for Tms {
-    // Custom impl because `Clone` is not implemented for `Proj`
-    fn clone(&self) -> Tms {
-        Tms::init(&self.tms).expect("Repeating initialization")
-    }
-}
-
 pub enum Matrix<'a> {
     Predefined(&'a TileMatrix),
     Calculated(TileMatrix),
@@ -79,44 +101,71 @@ pub enum ZoomLevelStrategy {
 impl Tms {
     /// Prepare transformations and check if TileMatrixSet supports quadkeys.
     pub(crate) fn init(data: &TileMatrixSet) -> Result<Self> {
-        let is_quadtree = check_quadkey_support(&data.tile_matrices);
+        if data.tile_matrices.is_empty() {
+            return Err(TmsError::NoTileMatrices);
+        }
         let data_crs = data.crs.clone();
         let geographic_crs = Crs::default(); // data.get("_geographic_crs", WGS84_CRS)
         let to_geographic = Transformer::from_crs(&data_crs, &geographic_crs, true).ok();
         let from_geographic = Transformer::from_crs(&geographic_crs, &data_crs, true).ok();
         let mut tms = data.clone();
-        Self::sort_tile_matrices(&mut tms)?;
-        // Check bounding box CRS (TODO: should we store it?)
-        if let Some(bounding_box) = &tms.bounding_box {
-            if let Some(crs) = &bounding_box.crs {
-                if *crs != tms.crs {
-                    let _transform = Transformer::from_crs(crs, &tms.crs, true)?;
-                }
+        let zooms = Self::sort_tile_matrices(&mut tms)?;
+        let is_quadtree = check_quadkey_support(&tms.tile_matrices);
+        let meters_per_unit = meters_per_unit(&data_crs).or_else(|err| {
+            // Derive the units from the scale of the first tile matrix
+            let matrix = &tms.tile_matrices[0];
+            let mpu = matrix.scale_denominator * 0.28e-3 / matrix.cell_size;
+            if mpu.is_finite() && mpu > 0.0 {
+                Ok(mpu)
+            } else {
+                Err(err)
             }
-        }
-        Ok(Self {
+        })?;
+        let axis_inverted = tms.crs_axis_inverted();
+        let mut tms = Self {
             tms,
             is_quadtree,
             data_crs,
             geographic_crs,
             to_geographic,
             from_geographic,
-        })
+            xy_bbox: BoundingBox::new(0.0, 0.0, 0.0, 0.0),
+            meters_per_unit,
+            axis_inverted,
+            zooms,
+        };
+        tms.xy_bbox = tms.calc_xy_bbox()?;
+        Ok(tms)
     }
 
-    /// Sort matrices by identifier
-    fn sort_tile_matrices(tms: &mut TileMatrixSet) -> Result<()> {
-        // Check zoom identifier format
-        for m in &tms.tile_matrices {
-            m.id.parse::<u8>()
-                .map_err(|_e| TmsError::InvalidZoomId(m.id.clone()))?;
+    /// Sort matrices and assign zoom levels.
+    ///
+    /// Numeric identifiers are used as zoom levels. If any identifier is not an
+    /// integer in the range 0-255 (e.g. `EPSG:3857:0` or `L00`), matrices are ordered
+    /// by decreasing scale denominator and numbered from 0.
+    fn sort_tile_matrices(tms: &mut TileMatrixSet) -> Result<Vec<u8>> {
+        let numeric_ids = tms
+            .tile_matrices
+            .iter()
+            .map(|m| m.id.parse::<u8>().ok())
+            .collect::<Option<Vec<_>>>();
+        if let Some(mut zooms) = numeric_ids {
+            tms.tile_matrices
+                .sort_by_key(|m| m.id.parse::<u8>().unwrap_or_default());
+            zooms.sort_unstable();
+            if let Some(w) = zooms.windows(2).find(|w| w[0] == w[1]) {
+                return Err(TmsError::InvalidZoomId(w[0].to_string()));
+            }
+            return Ok(zooms);
         }
-        tms.tile_matrices.sort_by(|a, b| {
-            a.id.parse::<u8>()
-                .unwrap()
-                .cmp(&b.id.parse::<u8>().unwrap())
-        });
-        Ok(())
+        if tms.tile_matrices.len() > usize::from(u8::MAX) + 1 {
+            return Err(TmsError::InvalidZoomId(
+                tms.tile_matrices.last().unwrap().id.clone(),
+            ));
+        }
+        tms.tile_matrices
+            .sort_by(|a, b| b.scale_denominator.total_cmp(&a.scale_denominator));
+        Ok((0..tms.tile_matrices.len()).map(|z| z as u8).collect())
     }
 
     /// Iterate over matrices
@@ -129,21 +178,23 @@ impl Tms {
         &self.tms.crs
     }
 
-    /// TileMatrixSet minimum TileMatrix identifier
+    /// CRS of geographic coordinates
+    pub fn geographic_crs(&self) -> &Crs {
+        &self.geographic_crs
+    }
+
+    /// TileMatrixSet minimum zoom level
     pub fn minzoom(&self) -> u8 {
-        self.tms.tile_matrices[0].id.parse::<u8>().unwrap()
+        self.zooms[0]
     }
-    /// TileMatrixSet maximum TileMatrix identifier
+    /// TileMatrixSet maximum zoom level
     pub fn maxzoom(&self) -> u8 {
-        self.tms.tile_matrices[self.tms.tile_matrices.len() - 1]
-            .id
-            .parse::<u8>()
-            .unwrap()
+        self.zooms[self.zooms.len() - 1]
     }
 
     /// Check if CRS has inverted AXIS (lat,lon) instead of (lon,lat).
-    fn invert_axis(&self) -> bool {
-        self.tms.crs_axis_inverted()
+    pub(crate) fn invert_axis(&self) -> bool {
+        self.axis_inverted
     }
 
     /// Construct a custom TileMatrixSet.
@@ -154,11 +205,11 @@ impl Tms {
     /// * `tile_width` - Width of each tile of this tile matrix in pixels (default is 256).
     /// * `tile_height` - Height of each tile of this tile matrix in pixels (default is 256).
     /// * `matrix_scale` - Tiling schema coalescence coefficient (default: [1, 1] for EPSG:3857).
-    ///     Should be set to [2, 1] for EPSG:4326.
-    ///     see: <http:///docs.opengeospatial.org/is/17-083r2/17-083r2.html#14>
+    ///   Should be set to [2, 1] for EPSG:4326.
+    ///   see: <http:///docs.opengeospatial.org/is/17-083r2/17-083r2.html#14>
     /// * `extent_crs` - pyproj.CRS
-    ///     Extent's coordinate reference system, as a pyproj CRS object.
-    ///     (default: same as input crs)
+    ///   Extent's coordinate reference system, as a pyproj CRS object.
+    ///   (default: same as input crs)
     /// * `minzoom` - Tile Matrix Set minimum zoom level (default is 0).
     /// * `maxzoom` - Tile Matrix Set maximum zoom level (default is 24).
     /// * `title` - Tile Matrix Set title (default is 'Custom TileMatrixSet')
@@ -263,15 +314,11 @@ impl Tms {
 
         let bbox = transformed_bbox(&extent, crs, extent_crs)?;
 
+        // Top left corner in CRS axis order
         let x_origin = if !is_inverted { bbox.left } else { bbox.top };
         let y_origin = if !is_inverted { bbox.top } else { bbox.left };
-        let corner_of_origin = if !is_inverted {
-            None
-        } else {
-            Some(CornerOfOrigin::BottomLeft)
-        };
 
-        let mpu = meters_per_unit(crs);
+        let mpu = meters_per_unit(crs)?;
         for (zoom, res) in resolutions.iter().enumerate() {
             let unitheight = tile_height as f64 * res;
             let unitwidth = tile_width as f64 * res;
@@ -286,7 +333,7 @@ impl Tms {
                 id: zoom.to_string(),
                 scale_denominator: res * mpu / 0.00028,
                 cell_size: *res,
-                corner_of_origin: corner_of_origin.clone(),
+                corner_of_origin: None,
                 point_of_origin: [x_origin, y_origin],
                 tile_width: NonZeroU16::new(tile_width).ok_or(TmsError::NonZeroError)?,
                 tile_height: NonZeroU16::new(tile_height).ok_or(TmsError::NonZeroError)?,
@@ -303,45 +350,76 @@ impl Tms {
 
     /// Return the TileMatrix for a specific zoom without automatic tile matrix extension.
     pub fn matrix_z(&self, zoom: u8) -> Option<&TileMatrix> {
-        self.tms
-            .tile_matrices
-            .iter()
-            .find(|&m| m.id == zoom.to_string())
+        let idx = self.zooms.binary_search(&zoom).ok()?;
+        Some(&self.tms.tile_matrices[idx])
+    }
+
+    /// Return the TileMatrix with the given identifier.
+    pub fn matrix_by_id(&self, id: &str) -> Option<&TileMatrix> {
+        self.tms.tile_matrices.iter().find(|m| m.id == id)
+    }
+
+    /// TileMatrix identifier of a zoom level.
+    ///
+    /// Returns `None` for zoom levels without predefined matrix.
+    pub fn matrix_id(&self, zoom: u8) -> Option<&str> {
+        self.matrix_z(zoom).map(|m| m.id.as_str())
+    }
+
+    /// Zoom level of the TileMatrix with the given identifier.
+    pub fn zoom_by_id(&self, id: &str) -> Option<u8> {
+        let idx = self.tms.tile_matrices.iter().position(|m| m.id == id)?;
+        Some(self.zooms[idx])
     }
 
     /// Return the TileMatrix for a specific zoom.
-    pub fn matrix(&self, zoom: u8) -> Matrix<'_> {
+    ///
+    /// Matrices beyond the maximum zoom level are extrapolated.
+    /// Their identifier is the zoom level.
+    pub fn matrix(&self, zoom: u8) -> Result<Matrix<'_>> {
         if let Some(m) = self.matrix_z(zoom) {
-            return Matrix::Predefined(m);
+            return Ok(Matrix::Predefined(m));
+        }
+        if zoom < self.maxzoom() {
+            return Err(TmsError::InvalidZoomError(zoom));
         }
 
-        let matrix_scale = (1..self.tms.tile_matrices.len())
+        // Extrapolation requires a uniform scale progression between levels.
+        // Ratios are compared with 2 decimals, like morecantile does.
+        let matrices = &self.tms.tile_matrices;
+        if matrices.len() < 2 {
+            return Err(TmsError::InvalidZoomError(zoom));
+        }
+        let mut matrix_scale = (1..matrices.len())
             .map(|idx| {
-                (self.tms.tile_matrices[idx].scale_denominator
-                    / self.tms.tile_matrices[idx - 1].scale_denominator)
-                    .round() // FIXME: round ndigits=2
+                (matrices[idx].scale_denominator / matrices[idx - 1].scale_denominator * 100.0)
+                    .round()
+                    / 100.0
             })
             .collect::<Vec<_>>();
-        if matrix_scale.len() > 1 {
-            // TODO: always true, error in morecantile?
-            // panic!(
-            //     "TileMatrix not found for level: {} - Unable to construct tileMatrix for TMS with variable scale",
-            //     zoom
-            // );
+        matrix_scale.dedup();
+        if matrix_scale.len() != 1 {
+            return Err(TmsError::VariableScale(zoom));
         }
 
-        let mut tile_matrix = self.tms.tile_matrices.last().unwrap().clone();
-        let factor = 1.0 / matrix_scale[0];
-        while tile_matrix.id != zoom.to_string() {
+        let mut tile_matrix = matrices.last().unwrap().clone();
+        // Unrounded ratio between the last two levels, e.g. 2.0 for a quadtree
+        let factor = matrices[matrices.len() - 2].scale_denominator / tile_matrix.scale_denominator;
+        if !factor.is_finite() || factor <= 1.0 {
+            return Err(TmsError::VariableScale(zoom));
+        }
+        let mut id = self.maxzoom();
+        while id < zoom {
+            id += 1;
             tile_matrix = TileMatrix {
                 title_description_keywords: TitleDescriptionKeywords {
                     title: None,
                     description: None,
                     keywords: None,
                 },
-                id: (tile_matrix.id.parse::<u8>().unwrap() + 1).to_string(),
+                id: id.to_string(),
                 scale_denominator: tile_matrix.scale_denominator / factor,
-                cell_size: tile_matrix.cell_size, // FIXME
+                cell_size: tile_matrix.cell_size / factor,
                 corner_of_origin: tile_matrix.corner_of_origin,
                 point_of_origin: tile_matrix.point_of_origin,
                 tile_width: tile_matrix.tile_width,
@@ -349,16 +427,15 @@ impl Tms {
                 matrix_width: NonZeroU64::new(
                     (u64::from(tile_matrix.matrix_width) as f64 * factor).round() as u64,
                 )
-                .unwrap(),
+                .ok_or(TmsError::NonZeroError)?,
                 matrix_height: NonZeroU64::new(
                     (u64::from(tile_matrix.matrix_height) as f64 * factor).round() as u64,
                 )
-                .unwrap(),
+                .ok_or(TmsError::NonZeroError)?,
                 variable_matrix_widths: None,
             }
         }
-
-        Matrix::Calculated(tile_matrix)
+        Ok(Matrix::Calculated(tile_matrix))
     }
 
     /// Tile resolution for a TileMatrix.
@@ -367,7 +444,7 @@ impl Tms {
     //   The pixel size of the tile can be obtained from the scaleDenominator
     //   by multiplying the later by 0.28 10-3 / metersPerUnit.
     pub fn resolution(&self, matrix: &TileMatrix) -> f64 {
-        matrix.scale_denominator * 0.28e-3 / meters_per_unit(self.crs())
+        matrix.scale_denominator * 0.28e-3 / self.meters_per_unit
     }
 
     /// Tile resolution for a specific zoom.
@@ -381,10 +458,10 @@ impl Tms {
     /// * `res` - Resolution in TMS unit.
     /// * `max_z` - Maximum zoom level (default is tms maxzoom).
     /// * `zoom_level_strategy` - Strategy to determine zoom level (same as in GDAL 3.2).
-    ///         LOWER will select the zoom level immediately below the theoretical computed non-integral zoom level.
-    ///         On the contrary, UPPER will select the immediately above zoom level.
-    ///         Defaults to AUTO which selects the closest zoom level.
-    ///         ref: <https://gdal.org/drivers/raster/cog.html#raster-cog>
+    ///   LOWER will select the zoom level immediately below the theoretical computed non-integral zoom level.
+    ///   On the contrary, UPPER will select the immediately above zoom level.
+    ///   Defaults to AUTO which selects the closest zoom level.
+    ///   ref: <https://gdal.org/drivers/raster/cog.html#raster-cog>
     /// * `min_z` - Minimum zoom level (default is tms minzoom).
     ///
     /// # Returns:
@@ -402,7 +479,7 @@ impl Tms {
         let mut matrix_res = 0.0;
         for z in min_z..=max_z {
             zoom_level = z;
-            matrix_res = self.resolution(self.matrix(zoom_level).as_ref());
+            matrix_res = self.resolution(self.matrix(zoom_level)?.as_ref());
             if res > matrix_res || (res - matrix_res).abs() / matrix_res <= 1e-8 {
                 break;
             }
@@ -416,7 +493,8 @@ impl Tms {
                     zoom_level = u8::min(zoom_level, max_z);
                 }
                 ZoomLevelStrategy::Auto => {
-                    if (self.resolution(self.matrix(u8::max(zoom_level - 1, min_z)).as_ref()) / res)
+                    if (self.resolution(self.matrix(u8::max(zoom_level - 1, min_z))?.as_ref())
+                        / res)
                         < (res / matrix_res)
                     {
                         zoom_level = u8::max(zoom_level - 1, min_z);
@@ -484,24 +562,24 @@ impl Tms {
 
     /// Get the tile containing a Point (in TMS CRS).
     ///
+    /// Tiles in coalesced rows are returned with their leftmost index.
+    ///
     /// # Arguments
     /// * `xcoord`, ycoord - A `X` and `Y` pair in TMS coordinate reference system.
     /// * `zoom` - The zoom level.
-    pub fn xy_tile(&self, xcoord: f64, ycoord: f64, zoom: u8) -> Xyz {
-        let m = self.matrix(zoom);
+    pub fn xy_tile(&self, xcoord: f64, ycoord: f64, zoom: u8) -> Result<Xyz> {
+        let cell = self.xy_cell(xcoord, ycoord, zoom)?;
+        // leftmost tile of coalesced tiles
+        let coalesce = matrix_coalescence(self.matrix(zoom)?.as_ref(), cell.y);
+        Ok(Xyz::new(cell.x / coalesce * coalesce, cell.y, zoom))
+    }
+
+    /// Get the matrix cell containing a Point (in TMS CRS), ignoring coalesced rows.
+    fn xy_cell(&self, xcoord: f64, ycoord: f64, zoom: u8) -> Result<Xyz> {
+        let m = self.matrix(zoom)?;
         let matrix = m.as_ref();
         let res = self.resolution(matrix);
-
-        let origin_x: f64 = if self.invert_axis() {
-            matrix.point_of_origin[1]
-        } else {
-            matrix.point_of_origin[0]
-        };
-        let origin_y = if self.invert_axis() {
-            matrix.point_of_origin[0]
-        } else {
-            matrix.point_of_origin[1]
-        };
+        let (origin_x, origin_y) = self.origin(matrix);
 
         let xtile = if !xcoord.is_infinite() {
             ((xcoord - origin_x) / (res * u16::from(matrix.tile_width) as f64)).floor()
@@ -509,7 +587,13 @@ impl Tms {
             0.0
         };
         let ytile = if !ycoord.is_infinite() {
-            ((origin_y - ycoord) / (res * u16::from(matrix.tile_height) as f64)).floor()
+            // Rows are counted upwards from a bottom left origin
+            let dy = if bottom_left_origin(matrix) {
+                ycoord - origin_y
+            } else {
+                origin_y - ycoord
+            };
+            (dy / (res * u16::from(matrix.tile_height) as f64)).floor()
         } else {
             0.0
         };
@@ -531,7 +615,7 @@ impl Tms {
             ytile
         };
 
-        Xyz::new(xtile, ytile, zoom)
+        Ok(Xyz::new(xtile, ytile, zoom))
     }
 
     /// Get the tile for a given geographic longitude and latitude pair.
@@ -541,7 +625,7 @@ impl Tms {
     /// * `zoom` : The zoom level.
     pub fn tile(&self, lng: f64, lat: f64, zoom: u8) -> Result<Xyz> {
         let xy = self.xy(lng, lat)?;
-        Ok(self.xy_tile(xy.x, xy.y, zoom))
+        self.xy_tile(xy.x, xy.y, zoom)
     }
 
     /// Get the tile for a given geographic longitude and latitude pair. Truncate inputs to limits of TMS geographic bounds.
@@ -551,42 +635,74 @@ impl Tms {
     /// * `zoom` : The zoom level.
     pub fn tile_truncated(&self, lng: f64, lat: f64, zoom: u8) -> Result<Xyz> {
         let xy = self.xy_truncated(lng, lat)?;
-        Ok(self.xy_tile(xy.x, xy.y, zoom))
+        self.xy_tile(xy.x, xy.y, zoom)
+    }
+
+    /// Get the matrix cell for a given geographic longitude and latitude pair, ignoring coalesced rows.
+    fn cell(&self, lng: f64, lat: f64, zoom: u8, truncate: bool) -> Result<Xyz> {
+        let xy = if truncate {
+            self.xy_truncated(lng, lat)?
+        } else {
+            self.xy(lng, lat)?
+        };
+        self.xy_cell(xy.x, xy.y, zoom)
     }
 
     /// Return the upper left coordinate of the tile in TMS coordinate reference system.
     ///
     /// # Arguments
     /// * `tile`: (x, y, z) tile coordinates or a Tile object we want the upper left coordinates of.
-    pub fn xy_ul(&self, tile: &Xyz) -> Coords {
-        let m = self.matrix(tile.z);
+    pub fn xy_ul(&self, tile: &Xyz) -> Result<Coords> {
+        let m = self.matrix(tile.z)?;
         let matrix = m.as_ref();
-        let res = self.resolution(matrix);
-
-        let origin_x = if self.invert_axis() {
-            matrix.point_of_origin[1]
-        } else {
-            matrix.point_of_origin[0]
-        };
-        let origin_y = if self.invert_axis() {
-            matrix.point_of_origin[0]
+        // The upper row border of a tile counted from the bottom is the border of the next row
+        let row = if bottom_left_origin(matrix) {
+            tile.y + 1
         } else {
-            matrix.point_of_origin[1]
+            tile.y
         };
-
-        let xcoord = origin_x + tile.x as f64 * res * u16::from(matrix.tile_width) as f64;
-        let ycoord = origin_y - tile.y as f64 * res * u16::from(matrix.tile_height) as f64;
-        Coords::new(xcoord, ycoord)
+        Ok(self.xy_corner(matrix, tile.x, row))
     }
 
     /// Return the bounding box of the tile in TMS coordinate reference system.
     ///
     /// # Arguments
     /// * `tile`: Tile object we want the bounding box of.
-    pub fn xy_bounds(&self, tile: &Xyz) -> BoundingBox {
-        let top_left = self.xy_ul(tile);
-        let bottom_right = self.xy_ul(&Xyz::new(tile.x + 1, tile.y + 1, tile.z));
-        BoundingBox::new(top_left.x, bottom_right.y, bottom_right.x, top_left.y)
+    pub fn xy_bounds(&self, tile: &Xyz) -> Result<BoundingBox> {
+        let m = self.matrix(tile.z)?;
+        let matrix = m.as_ref();
+        let coalesce = matrix_coalescence(matrix, tile.y);
+        let first = self.xy_corner(matrix, tile.x, tile.y);
+        let last = self.xy_corner(matrix, tile.x + coalesce, tile.y + 1);
+        Ok(BoundingBox::new(
+            first.x.min(last.x),
+            first.y.min(last.y),
+            first.x.max(last.x),
+            first.y.max(last.y),
+        ))
+    }
+
+    /// Origin of a matrix in (x, y) order.
+    fn origin(&self, matrix: &TileMatrix) -> (f64, f64) {
+        if self.invert_axis() {
+            (matrix.point_of_origin[1], matrix.point_of_origin[0])
+        } else {
+            (matrix.point_of_origin[0], matrix.point_of_origin[1])
+        }
+    }
+
+    /// Coordinates of the grid corner at column `col` and row `row`, counted from the origin.
+    pub(crate) fn xy_corner(&self, matrix: &TileMatrix, col: u64, row: u64) -> Coords {
+        let res = self.resolution(matrix);
+        let (origin_x, origin_y) = self.origin(matrix);
+        let xcoord = origin_x + col as f64 * res * u16::from(matrix.tile_width) as f64;
+        let dy = row as f64 * res * u16::from(matrix.tile_height) as f64;
+        let ycoord = if bottom_left_origin(matrix) {
+            origin_y + dy
+        } else {
+            origin_y - dy
+        };
+        Coords::new(xcoord, ycoord)
     }
 
     /// Return the upper left coordinates of the tile in geographic coordinate reference system.
@@ -594,11 +710,11 @@ impl Tms {
     /// # Arguments
     /// * `tile` - (x, y, z) tile coordinates or a Tile object we want the upper left geographic coordinates of.
     pub fn ul(&self, tile: &Xyz) -> Result<Coords> {
-        let coords = if self.data_crs.as_srid() == 3857 && self.geographic_crs.as_srid() == 4326 {
+        let coords = if self.is_web_mercator_grid(tile.z)? {
             let (lon, lat) = merc_tile_ul(tile.x as u32, tile.y as u32, tile.z);
             Coords::new(lon, lat)
         } else {
-            let xy = self.xy_ul(tile);
+            let xy = self.xy_ul(tile)?;
             self.lnglat(xy.x, xy.y, false)?
         };
         Ok(coords)
@@ -609,8 +725,17 @@ impl Tms {
     /// # Arguments
     /// * `tile` - Tile object we want the bounding box of.
     pub fn bounds(&self, tile: &Xyz) -> Result<BoundingBox> {
-        let top_left = self.ul(tile)?;
-        let bottom_right = self.ul(&Xyz::new(tile.x + 1, tile.y + 1, tile.z))?;
+        let (top_left, bottom_right) = if self.is_web_mercator_grid(tile.z)? {
+            let coalesce = matrix_coalescence(self.matrix(tile.z)?.as_ref(), tile.y);
+            let lower_right_neighbor = Xyz::new(tile.x + coalesce, tile.y + 1, tile.z);
+            (self.ul(tile)?, self.ul(&lower_right_neighbor)?)
+        } else {
+            let xy = self.xy_bounds(tile)?;
+            (
+                self.lnglat(xy.left, xy.top, false)?,
+                self.lnglat(xy.right, xy.bottom, false)?,
+            )
+        };
         Ok(BoundingBox::new(
             top_left.x,
             bottom_right.y,
@@ -619,8 +744,20 @@ impl Tms {
         ))
     }
 
+    /// Check for a Web Mercator matrix with top left origin, supporting direct tile computation.
+    fn is_web_mercator_grid(&self, zoom: u8) -> Result<bool> {
+        Ok(self.data_crs == Crs::from_epsg(3857)
+            && (self.geographic_crs == Crs::default()
+                || self.geographic_crs == Crs::from_epsg(4326))
+            && !bottom_left_origin(self.matrix(zoom)?.as_ref()))
+    }
+
     /// Return TMS bounding box in TileMatrixSet's CRS.
     pub fn xy_bbox(&self) -> BoundingBox {
+        self.xy_bbox
+    }
+
+    fn calc_xy_bbox(&self) -> Result<BoundingBox> {
         let (left, bottom, right, top) = if let Some(bounding_box) = &self.tms.bounding_box {
             let (left, bottom) = if self.invert_axis() {
                 (&bounding_box.lower_left[1], &bounding_box.lower_left[0])
@@ -634,13 +771,8 @@ impl Tms {
             };
             if let Some(crs) = &bounding_box.crs {
                 if crs != self.crs() {
-                    // Verified in init function
-                    let transform =
-                        Transformer::from_crs(crs, self.crs(), true).expect("Transformer");
-                    let (left, bottom, right, top) = transform
-                        .transform_bounds(*left, *bottom, *right, *top /* , Some(21) */)
-                        .expect("Transformer");
-                    (left, bottom, right, top)
+                    let transform = Transformer::from_crs(crs, self.crs(), true)?;
+                    transform.transform_bounds(*left, *bottom, *right, *top)?
                 } else {
                     (*left, *bottom, *right, *top)
                 }
@@ -649,22 +781,27 @@ impl Tms {
             }
         } else {
             let zoom = self.minzoom();
-            let m = self.matrix(zoom);
+            let m = self.matrix(zoom)?;
             let matrix = m.as_ref();
-            let top_left = self.xy_ul(&Xyz::new(0, 0, zoom));
-            let bottom_right = self.xy_ul(&Xyz::new(
+            let origin = self.xy_corner(matrix, 0, 0);
+            let corner = self.xy_corner(
+                matrix,
                 u64::from(matrix.matrix_width),
                 u64::from(matrix.matrix_height),
-                zoom,
-            ));
-            (top_left.x, bottom_right.y, bottom_right.x, top_left.y)
+            );
+            (
+                origin.x.min(corner.x),
+                origin.y.min(corner.y),
+                origin.x.max(corner.x),
+                origin.y.max(corner.y),
+            )
         };
-        BoundingBox {
+        Ok(BoundingBox {
             left,
             bottom,
             right,
             top,
-        }
+        })
     }
 
     /// Return TMS bounding box in geographic coordinate reference system.
@@ -684,11 +821,7 @@ impl Tms {
 
     /// Check if a bounds intersects with the TMS bounds.
     pub fn intersect_tms(&self, bbox: &BoundingBox) -> bool {
-        let tms_bounds = self.xy_bbox();
-        bbox.left < tms_bounds.right
-            && bbox.right > tms_bounds.left
-            && bbox.top > tms_bounds.bottom
-            && bbox.bottom < tms_bounds.top
+        bbox.intersects(&self.xy_bbox)
     }
 
     /// Get the tiles overlapped by a geographic bounding box
@@ -714,99 +847,117 @@ impl Tms {
     ) -> Result<impl Iterator<Item = Xyz>> {
         let mut tiles: Vec<Xyz> = Vec::new();
         let bbox = self.bbox()?;
-        let bboxes = if west > east {
-            vec![
-                (bbox.left, south, east, north),
-                (west, south, bbox.right, north),
-            ]
-        } else {
-            vec![(west, south, east, north)]
-        };
-        let get_tile = if truncate {
-            Tms::tile_truncated
-        } else {
-            Tms::tile
-        };
-        for bb in bboxes {
-            let w = bb.0.max(bbox.left);
-            let s = bb.1.max(bbox.bottom);
-            let e = bb.2.min(bbox.right);
-            let n = bb.3.min(bbox.top);
+        let extend = BoundingBox::new(west, south, east, north);
+        for bb in clipped_extents(&extend, &bbox) {
             for z in zooms {
-                let ul_tile = get_tile(self, w + LL_EPSILON, n - LL_EPSILON, *z)?;
-                let lr_tile = get_tile(self, e - LL_EPSILON, s + LL_EPSILON, *z)?;
-                for i in ul_tile.x..=lr_tile.x {
-                    for j in ul_tile.y..=lr_tile.y {
-                        tiles.push(Xyz::new(i, j, *z));
-                    }
-                }
+                let limits = self.geographic_limits(&bb, *z, truncate)?;
+                tiles.extend(limits.tiles());
             }
         }
         Ok(tiles.into_iter())
     }
 
+    /// Get the tile limits overlapped by a clipped geographic bounding box
+    fn geographic_limits(&self, bbox: &BoundingBox, zoom: u8, truncate: bool) -> Result<TileRange> {
+        let ul_tile = self.cell(
+            bbox.left + LL_EPSILON,
+            bbox.top - LL_EPSILON,
+            zoom,
+            truncate,
+        )?;
+        let lr_tile = self.cell(
+            bbox.right - LL_EPSILON,
+            bbox.bottom + LL_EPSILON,
+            zoom,
+            truncate,
+        )?;
+        self.limits(zoom, &ul_tile, &lr_tile)
+    }
+
     /// Get the tile limits overlapped by a geographic bounding box
-    fn extent_limits(
+    ///
+    /// Extents crossing the antimeridian (`left > right`) return two limits per zoom level.
+    pub(crate) fn extent_limits(
         &self,
         extend: &BoundingBox,
         minzoom: u8,
         maxzoom: u8,
         truncate: bool, /* = False */
-    ) -> Result<Vec<MinMax>> {
-        if extend.left > extend.right || minzoom > maxzoom {
-            return Ok(Vec::new()); // TODO: Handle extend over date line
+    ) -> Result<Vec<TileRange>> {
+        if minzoom > maxzoom {
+            return Ok(Vec::new());
+        }
+        let extents = clipped_extents(extend, &self.bbox()?);
+        let mut limits = Vec::new();
+        for z in minzoom..=maxzoom {
+            for bbox in &extents {
+                limits.push(self.geographic_limits(bbox, z, truncate)?);
+            }
         }
-        let bbox = self.bbox()?;
-        let get_tile = if truncate {
-            Tms::tile_truncated
-        } else {
-            Tms::tile
-        };
-        let w = extend.left.max(bbox.left);
-        let s = extend.bottom.max(bbox.bottom);
-        let e = extend.right.min(bbox.right);
-        let n = extend.top.min(bbox.top);
-        let limits = (minzoom..=maxzoom)
-            .map(|z| {
-                let ul_tile = get_tile(self, w + LL_EPSILON, n - LL_EPSILON, z)?;
-                let lr_tile = get_tile(self, e - LL_EPSILON, s + LL_EPSILON, z)?;
-                Ok(MinMax {
-                    x_min: ul_tile.x,
-                    x_max: lr_tile.x,
-                    y_min: ul_tile.y,
-                    y_max: lr_tile.y,
-                })
-            })
-            .collect::<Result<Vec<MinMax>>>()?;
         Ok(limits)
     }
 
-    /// Get the tile limits overlapped by a bounding box in TMS CRS
-    fn extent_limits_xy(&self, extend: &BoundingBox, minzoom: u8, maxzoom: u8) -> Vec<MinMax> {
-        if extend.left > extend.right || minzoom > maxzoom {
-            return Vec::new(); // TODO: Handle extend over date line
+    /// Get the tile ranges overlapped by a geographic bounding box
+    ///
+    /// # Arguments
+    /// * `bbox` - Geographic bounding box.
+    /// * `minzoom` - Minimum zoom level.
+    /// * `maxzoom` - Maximum zoom level.
+    /// * `buffer` - Number of tiles added on each side, clipped to the tile matrix.
+    ///
+    /// Extents crossing the antimeridian (`left > right`) return two ranges per zoom level.
+    /// Columns of coalesced rows are extended to the boundaries of coalesced tiles.
+    pub fn tile_limits(
+        &self,
+        bbox: &BoundingBox,
+        minzoom: u8,
+        maxzoom: u8,
+        buffer: u64,
+    ) -> Result<Vec<TileRange>> {
+        let mut limits = self.extent_limits(bbox, minzoom, maxzoom, false)?;
+        if buffer > 0 {
+            for limit in &mut limits {
+                let matrix = self.matrix_range(limit.z)?;
+                limit.x_min = limit.x_min.saturating_sub(buffer);
+                limit.x_max = limit.x_max.saturating_add(buffer).min(matrix.x_max);
+                limit.y_min = limit.y_min.saturating_sub(buffer);
+                limit.y_max = limit.y_max.saturating_add(buffer).min(matrix.y_max);
+            }
         }
-        let bbox = self.xy_bbox();
-        let w = extend.left.max(bbox.left);
-        let s = extend.bottom.max(bbox.bottom);
-        let e = extend.right.min(bbox.right);
-        let n = extend.top.min(bbox.top);
-        (minzoom..=maxzoom)
-            .map(|z| {
-                let res = self.resolution(self.matrix(z).as_ref()) / 10.0;
-                let ul_tile = self.xy_tile(w + res, n - res, z);
-                let lr_tile = self.xy_tile(e - res, s + res, z);
-                MinMax {
-                    x_min: ul_tile.x,
-                    x_max: lr_tile.x,
-                    y_min: ul_tile.y,
-                    y_max: lr_tile.y,
-                }
-            })
+        limits
+            .into_iter()
+            .map(|limit| limit.aligned(self))
             .collect()
     }
 
+    /// Get the tile limits overlapped by a bounding box in TMS CRS
+    ///
+    /// Extents crossing the antimeridian (`left > right`) return two limits per zoom level.
+    fn extent_limits_xy(
+        &self,
+        extend: &BoundingBox,
+        minzoom: u8,
+        maxzoom: u8,
+    ) -> Result<Vec<TileRange>> {
+        if minzoom > maxzoom {
+            return Ok(Vec::new());
+        }
+        let extents = clipped_extents(extend, &self.xy_bbox());
+        let mut limits = Vec::new();
+        for z in minzoom..=maxzoom {
+            let res = self.resolution(self.matrix(z)?.as_ref()) / 10.0;
+            for bbox in &extents {
+                let ul_tile = self.xy_cell(bbox.left + res, bbox.top - res, z)?;
+                let lr_tile = self.xy_cell(bbox.right - res, bbox.bottom + res, z)?;
+                limits.push(self.limits(z, &ul_tile, &lr_tile)?);
+            }
+        }
+        Ok(limits)
+    }
+
     /// Get iterator over all tiles overlapped by a geographic bounding box
+    ///
+    /// Bounding boxes crossing the antimeridian have `left > right`.
     pub fn xyz_iterator_geographic(
         &self,
         extend: &BoundingBox,
@@ -814,128 +965,284 @@ impl Tms {
         maxzoom: u8,
     ) -> Result<XyzIterator> {
         let limits = self.extent_limits(extend, minzoom, maxzoom, false)?;
-        Ok(XyzIterator::new(minzoom, maxzoom, limits))
+        Ok(XyzIterator::new(limits))
     }
 
     /// Get iterator over all tiles overlapped by a bounding box in TMS CRS
-    pub fn xyz_iterator(&self, extend: &BoundingBox, minzoom: u8, maxzoom: u8) -> XyzIterator {
-        let limits = self.extent_limits_xy(extend, minzoom, maxzoom);
-        XyzIterator::new(minzoom, maxzoom, limits)
-    }
-
-    // def feature(
-    //     self,
-    //     tile: Tile,
-    //     fid: Optional[str] = None,
-    //     props: Optional[Dict] = None,
-    //     buffer: Optional[NumType] = None,
-    //     precision: Optional[int] = None,
-    //     projected: bool = False,
-    // ) -> Dict:
-    //     """
-    //     Get the GeoJSON feature corresponding to a tile.
-    //
-    //     Originally from <https://github.com/mapbox/mercantile/blob/master/mercantile/__init__.py>
-    //
-    //     Parameters
-    //     ----------
-    //     tile : Tile or sequence of int
-    //         May be be either an instance of Tile or 3 ints, X, Y, Z.
-    //     fid : str, optional
-    //         A feature id.
-    //     props : dict, optional
-    //         Optional extra feature properties.
-    //     buffer : float, optional
-    //         Optional buffer distance for the GeoJSON polygon.
-    //     precision: float
-    //         If >= 0, geometry coordinates will be rounded to this number of decimal,
-    //         otherwise original coordinate values will be preserved (default).
-    //     projected : bool, optional
-    //         Return coordinates in TMS projection. Default is false.
-    //
-    //     Returns
-    //     -------
-    //     dict
-    //
-    //     """
-    //     west, south, east, north = self.xy_bounds(tile)
-    //
-    //     if not projected:
-    //         west, south, east, north = self._to_geographic.transform_bounds(
-    //             west, south, east, north, densify_pts=21
-    //         )
-    //
-    //     if buffer:
-    //         west -= buffer
-    //         south -= buffer
-    //         east += buffer
-    //         north += buffer
-    //
-    //     if precision and precision >= 0:
-    //         west, south, east, north = (
-    //             round(v, precision) for v in (west, south, east, north)
-    //         )
-    //
-    //     bbox = [min(west, east), min(south, north), max(west, east), max(south, north)]
-    //     geom = bbox_to_feature(west, south, east, north)
-    //
-    //     xyz = str(tile)
-    //     feat: Dict[str, Any] = {
-    //         "type": "Feature",
-    //         "bbox": bbox,
-    //         "id": xyz,
-    //         "geometry": geom,
-    //         "properties": {
-    //             "title": f"XYZ tile {xyz}",
-    //             "grid_name": self.identifier,
-    //             "grid_crs": self.crs.to_string(),
-    //         },
-    //     }
-    //
-    //     if projected:
-    //         warnings.warn(
-    //             "CRS is no longer part of the GeoJSON specification."
-    //             "Other projection than EPSG:4326 might not be supported.",
-    //             UserWarning,
-    //         )
-    //         feat.update(
-    //             {"crs": {"type": "EPSG", "properties": {"code": self.crs.to_epsg()}}}
-    //         )
-    //
-    //     if props:
-    //         feat["properties"].update(props)
-    //
-    //     if fid is not None:
-    //         feat["id"] = fid
+    ///
+    /// Bounding boxes crossing the antimeridian have `left > right`.
+    pub fn xyz_iterator(
+        &self,
+        extend: &BoundingBox,
+        minzoom: u8,
+        maxzoom: u8,
+    ) -> Result<XyzIterator> {
+        let limits = self.extent_limits_xy(extend, minzoom, maxzoom)?;
+        Ok(XyzIterator::new(limits))
+    }
+
+    /// Get iterator over all tiles overlapped by a bounding box in TMS CRS, visiting each zoom
+    /// level in the order of a space-filling curve.
+    ///
+    /// Bounding boxes crossing the antimeridian have `left > right`.
+    pub fn xyz_iterator_curve(
+        &self,
+        extend: &BoundingBox,
+        minzoom: u8,
+        maxzoom: u8,
+        curve: Curve,
+    ) -> Result<CurveIterator> {
+        let limits = self.extent_limits_xy(extend, minzoom, maxzoom)?;
+        let orders = self.curve_orders(&limits)?;
+        Ok(CurveIterator::new(curve, limits, orders, false))
+    }
+
+    /// Get iterator over all tiles overlapped by a bounding box in TMS CRS, visiting all zoom
+    /// levels in the order of a space-filling curve at `maxzoom`.
+    ///
+    /// Each tile is followed by its children. Only quadtree tile matrix sets are supported.
+    pub fn xyz_iterator_curve_global(
+        &self,
+        extend: &BoundingBox,
+        minzoom: u8,
+        maxzoom: u8,
+        curve: Curve,
+    ) -> Result<CurveIterator> {
+        if !self.is_quadtree {
+            return Err(TmsError::NoQuadkeySupport);
+        }
+        let limits = self.extent_limits_xy(extend, minzoom, maxzoom)?;
+        let orders = self.curve_orders(&limits)?;
+        Ok(CurveIterator::new(curve, limits, orders, true))
+    }
+
+    /// Visit all tiles overlapped by a bounding box in TMS CRS depth-first, starting with the
+    /// tiles of `minzoom`.
+    ///
+    /// Each tile is visited before its children. The visitor decides whether to descend into
+    /// the children of a tile, to skip them or to stop the traversal.
+    /// Tiles overlapping multiple parents are visited as children of the parent containing their
+    /// center.
+    pub fn visit_pyramid<F>(
+        &self,
+        extend: &BoundingBox,
+        minzoom: u8,
+        maxzoom: u8,
+        mut visitor: F,
+    ) -> Result<()>
+    where
+        F: FnMut(&Xyz) -> TileVisit,
+    {
+        let limits = self.extent_limits_xy(extend, minzoom, maxzoom)?;
+        // Skip tiles of overlapping limits of extents crossing the antimeridian
+        let mut roots = limits
+            .iter()
+            .enumerate()
+            .filter(|(_, limit)| limit.z == minzoom)
+            .flat_map(|(i, limit)| {
+                let previous = &limits[..i];
+                limit
+                    .tiles()
+                    .filter(move |tile| !previous.iter().any(|other| other.contains(tile)))
+            });
+        let mut stack = Vec::new();
+        while let Some(tile) = stack.pop().or_else(|| roots.next()) {
+            match visitor(&tile) {
+                TileVisit::Continue => {}
+                TileVisit::SkipChildren => continue,
+                TileVisit::Stop => break,
+            }
+            if tile.z >= maxzoom {
+                continue;
+            }
+            let mut children = Vec::new();
+            for child in self.children(&tile, None)? {
+                if limits.iter().any(|limit| limit.contains(&child))
+                    && (self.is_quadtree || self.primary_parent(&child)? == tile)
+                {
+                    children.push(child);
+                }
+            }
+            stack.extend(children.into_iter().rev());
+        }
+        Ok(())
+    }
+
+    /// Parent tile containing the center of a tile.
+    fn primary_parent(&self, tile: &Xyz) -> Result<Xyz> {
+        let center = self.xy_bounds(tile)?.center();
+        self.xy_tile(center.x, center.y, tile.z - 1)
+    }
+
+    fn curve_orders(&self, limits: &[TileRange]) -> Result<Vec<u8>> {
+        limits
+            .iter()
+            .map(|limit| {
+                let matrix = self.matrix(limit.z)?;
+                let matrix = matrix.as_ref();
+                let size = u64::from(matrix.matrix_width).max(u64::from(matrix.matrix_height));
+                let order = 64 - (size - 1).leading_zeros();
+                if order > 32 {
+                    return Err(TmsError::InvalidZoom(limit.z));
+                }
+                Ok(order as u8)
+            })
+            .collect()
+    }
+
+    /// Get the GeoJSON feature corresponding to a tile.
     //
-    //     return feat
+    // Originally from <https://github.com/mapbox/mercantile/blob/master/mercantile/__init__.py>
+    ///
+    /// # Arguments
+    /// * `tile` - Tile object we want the feature of.
+    /// * `fid` - A feature id (default is the tile index).
+    /// * `props` - Optional extra feature properties.
+    /// * `buffer` - Optional buffer distance for the GeoJSON polygon.
+    /// * `precision` - Number of decimals of geometry coordinates.
+    ///   Original coordinate values are preserved if `None`.
+    /// * `projected` - Return coordinates in TMS projection.
+    ///   CRS is no longer part of the GeoJSON specification.
+    ///   Other projections than EPSG:4326 might not be supported by clients.
+    pub fn feature(
+        &self,
+        tile: &Xyz,
+        fid: Option<&str>,
+        props: Option<serde_json::Map<String, serde_json::Value>>,
+        buffer: Option<f64>,
+        precision: Option<u8>,
+        projected: bool,
+    ) -> Result<serde_json::Value> {
+        let bounds = if projected {
+            self.xy_bounds(tile)?
+        } else {
+            self.bounds(tile)?
+        };
+        let (mut west, mut south, mut east, mut north) =
+            (bounds.left, bounds.bottom, bounds.right, bounds.top);
+
+        if let Some(buffer) = buffer {
+            west -= buffer;
+            south -= buffer;
+            east += buffer;
+            north += buffer;
+        }
+
+        if let Some(precision) = precision {
+            let factor = 10.0_f64.powi(precision as i32);
+            [west, south, east, north] =
+                [west, south, east, north].map(|v| (v * factor).round() / factor);
+        }
+
+        let bbox = [
+            west.min(east),
+            south.min(north),
+            west.max(east),
+            south.max(north),
+        ];
+        let geom = bbox_to_feature(west, south, east, north);
+
+        let xyz = format!("Tile(x={}, y={}, z={})", tile.x, tile.y, tile.z);
+        let mut properties = serde_json::Map::new();
+        properties.insert("title".to_string(), format!("XYZ tile {xyz}").into());
+        properties.insert("grid_name".to_string(), self.tms.id.clone().into());
+        properties.insert("grid_crs".to_string(), self.crs().to_string().into());
+        if let Some(props) = props {
+            properties.extend(props);
+        }
+
+        let mut feat = json!({
+            "type": "Feature",
+            "bbox": bbox,
+            "id": fid.unwrap_or(&xyz),
+            "geometry": geom,
+            "properties": properties,
+        });
+
+        if projected {
+            let crs = self.crs();
+            let srid = match crs.authority {
+                Authority::EPSG => crs.code.parse::<i32>().ok(),
+                Authority::OGC if crs.code == "CRS84" => Some(4326),
+                Authority::OGC => None,
+            };
+            feat["crs"] = if let Some(srid) = srid {
+                json!({"type": "EPSG", "properties": {"code": srid}})
+            } else {
+                json!({"type": "name", "properties": {"name": crs.to_urn()}})
+            };
+        }
+
+        Ok(feat)
+    }
+
+    /// Get the GeoJSON FeatureCollection of tiles.
+    ///
+    /// # Arguments
+    /// * `tiles` - Tiles, e.g. from a [XyzIterator].
+    /// * `projected` - Return coordinates in TMS projection.
+    pub fn feature_collection<I>(&self, tiles: I, projected: bool) -> Result<serde_json::Value>
+    where
+        I: IntoIterator<Item = Xyz>,
+    {
+        let features = tiles
+            .into_iter()
+            .map(|tile| self.feature(&tile, None, None, None, None, projected))
+            .collect::<Result<Vec<_>>>()?;
+        Ok(json!({
+            "type": "FeatureCollection",
+            "features": features,
+        }))
+    }
+
+    /// Tile limits between an upper left and a lower right matrix cell.
+    ///
+    /// Rows are swapped for matrices with a bottom left origin.
+    fn limits(&self, zoom: u8, ul_tile: &Xyz, lr_tile: &Xyz) -> Result<TileRange> {
+        let matrix = self.matrix(zoom)?;
+        Ok(TileRange {
+            z: zoom,
+            x_min: ul_tile.x.min(lr_tile.x),
+            x_max: ul_tile.x.max(lr_tile.x),
+            y_min: ul_tile.y.min(lr_tile.y),
+            y_max: ul_tile.y.max(lr_tile.y),
+            variable_matrix_widths: matrix
+                .as_ref()
+                .variable_matrix_widths
+                .clone()
+                .unwrap_or_default(),
+        })
+    }
 
     /// Return TileMatrix Extrema.
     ///
     /// # Arguments
     /// * `zoom` - The zoom level.
-    fn minmax(&self, zoom: u8) -> MinMax {
-        let matrix = self.matrix(zoom);
+    pub fn matrix_range(&self, zoom: u8) -> Result<TileRange> {
+        let matrix = self.matrix(zoom)?;
         let m = matrix.as_ref();
-        MinMax {
+        Ok(TileRange {
+            z: zoom,
             x_min: 0,
             x_max: u64::from(m.matrix_width).saturating_sub(1),
             y_min: 0,
             y_max: u64::from(m.matrix_height).saturating_sub(1),
-        }
+            variable_matrix_widths: m.variable_matrix_widths.clone().unwrap_or_default(),
+        })
     }
 
     /// Check if a tile is valid.
+    ///
+    /// Tiles in coalesced rows are only valid for the leftmost tile index.
     pub fn is_valid(&self, tile: &Xyz) -> bool {
-        if tile.z < self.minzoom() {
+        let Ok(extrema) = self.matrix_range(tile.z) else {
             return false;
-        }
-
-        let extrema = self.minmax(tile.z);
+        };
         let validx = extrema.x_min <= tile.x && tile.x <= extrema.x_max;
         let validy = extrema.y_min <= tile.y && tile.y <= extrema.y_max;
+        let leftmost = tile.x % extrema.coalescence(tile.y) == 0;
 
-        validx && validy
+        validx && validy && leftmost
     }
 
     /// The neighbors of a tile
@@ -949,17 +1256,24 @@ impl Tms {
     /// # Arguments
     /// * `tile` - instance of Tile
     pub fn neighbors(&self, tile: &Xyz) -> Vec<Xyz> {
-        let extrema = self.minmax(tile.z);
+        let Ok(extrema) = self.matrix_range(tile.z) else {
+            return Vec::new();
+        };
+        let x_min = tile.x.saturating_sub(1).max(extrema.x_min);
+        let x_max = tile
+            .x
+            .saturating_add(extrema.coalescence(tile.y))
+            .min(extrema.x_max);
 
         let mut tiles = Vec::new();
-        for x in tile.x.saturating_sub(1)..=tile.x.saturating_add(1) {
-            for y in tile.y.saturating_sub(1)..=tile.y.saturating_add(1) {
-                if x == tile.x && y == tile.y
-                    || x < extrema.x_min
-                    || y < extrema.y_min
-                    || x > extrema.x_max
-                    || y > extrema.y_max
-                {
+        for y in tile.y.saturating_sub(1)..=tile.y.saturating_add(1) {
+            if y < extrema.y_min || y > extrema.y_max {
+                continue;
+            }
+            // Neighbors in coalesced rows may cover more than one column
+            let coalesce = extrema.coalescence(y);
+            for x in (x_min / coalesce * coalesce..=x_max).step_by(coalesce as usize) {
+                if x == tile.x && y == tile.y {
                     continue;
                 }
 
@@ -978,7 +1292,7 @@ impl Tms {
     /// # Arguments
     /// * `tile` - instance of Tile
     /// * `zoom` - Determines the *zoom* level of the returned parent tile.
-    ///     This defaults to one lower than the tile (the immediate parent).
+    ///   This defaults to one lower than the tile (the immediate parent).
     pub fn parent(&self, tile: &Xyz, zoom: Option<u8> /*  = None */) -> Result<Vec<Xyz>> {
         if tile.z == self.minzoom() {
             return Ok(vec![]);
@@ -998,18 +1312,16 @@ impl Tms {
             None => tile.z - 1,
         };
 
-        let res = self.resolution(self.matrix(tile.z).as_ref()) / 10.0;
+        let res = self.resolution(self.matrix(tile.z)?.as_ref()) / 10.0;
 
-        let bbox = self.xy_bounds(tile);
-        let ul_tile = self.xy_tile(bbox.left + res, bbox.top - res, target_zoom);
-        let lr_tile = self.xy_tile(bbox.right - res, bbox.bottom + res, target_zoom);
+        let bbox = self.xy_bounds(tile)?;
+        let ul_tile = self.xy_cell(bbox.left + res, bbox.top - res, target_zoom)?;
+        let lr_tile = self.xy_cell(bbox.right - res, bbox.bottom + res, target_zoom)?;
 
-        let mut tiles = Vec::new();
-        for i in ul_tile.x..=lr_tile.x {
-            for j in ul_tile.y..=lr_tile.y {
-                tiles.push(Xyz::new(i, j, target_zoom));
-            }
-        }
+        let tiles = self
+            .limits(target_zoom, &ul_tile, &lr_tile)?
+            .tiles()
+            .collect();
 
         Ok(tiles)
     }
@@ -1021,10 +1333,8 @@ impl Tms {
     /// # Arguments
     /// * `tile` - instance of Tile
     /// * `zoom` - Determines the *zoom* level of the returned parent tile.
-    ///     This defaults to one lower than the tile (the immediate parent).
+    ///   This defaults to one lower than the tile (the immediate parent).
     pub fn children(&self, tile: &Xyz, zoom: Option<u8>) -> Result<Vec<Xyz>> {
-        let mut tiles = Vec::new();
-
         if let Some(zoom) = zoom {
             if tile.z > zoom {
                 // zoom must be greater than that of the input tile
@@ -1034,20 +1344,19 @@ impl Tms {
 
         let target_zoom = match zoom {
             Some(z) => z,
-            None => tile.z + 1,
+            None => tile.z.checked_add(1).ok_or(TmsError::InvalidZoom(tile.z))?,
         };
 
-        let bbox = self.xy_bounds(tile);
-        let res = self.resolution(self.matrix(tile.z).as_ref()) / 10.0;
+        let bbox = self.xy_bounds(tile)?;
+        let res = self.resolution(self.matrix(tile.z)?.as_ref()) / 10.0;
 
-        let ul_tile = self.xy_tile(bbox.left + res, bbox.top - res, target_zoom);
-        let lr_tile = self.xy_tile(bbox.right - res, bbox.bottom + res, target_zoom);
+        let ul_tile = self.xy_cell(bbox.left + res, bbox.top - res, target_zoom)?;
+        let lr_tile = self.xy_cell(bbox.right - res, bbox.bottom + res, target_zoom)?;
 
-        for i in ul_tile.x..=lr_tile.x {
-            for j in ul_tile.y..=lr_tile.y {
-                tiles.push(Xyz::new(i, j, target_zoom));
-            }
-        }
+        let tiles = self
+            .limits(target_zoom, &ul_tile, &lr_tile)?
+            .tiles()
+            .collect();
 
         Ok(tiles)
     }
@@ -1061,12 +1370,12 @@ impl Tms {
     }
 }
 
-#[derive(Debug)]
-pub(crate) struct MinMax {
-    pub x_min: u64,
-    pub x_max: u64,
-    pub y_min: u64,
-    pub y_max: u64,
+/// Number of tiles coalescing in a single tile for a row of a TileMatrix.
+fn matrix_coalescence(matrix: &TileMatrix, row: u64) -> u64 {
+    coalescence(
+        matrix.variable_matrix_widths.as_deref().unwrap_or_default(),
+        row,
+    )
 }
 
 impl TryFrom<&TileMatrixSet> for Tms {
@@ -1076,13 +1385,40 @@ impl TryFrom<&TileMatrixSet> for Tms {
     }
 }
 
+/// Check if the rows of a matrix are counted upwards from a bottom left origin
+pub(crate) fn bottom_left_origin(matrix: &TileMatrix) -> bool {
+    matches!(matrix.corner_of_origin, Some(CornerOfOrigin::BottomLeft))
+}
+
+/// Split an extent crossing the antimeridian and clip it to the given bounds.
+fn clipped_extents(extend: &BoundingBox, bounds: &BoundingBox) -> Vec<BoundingBox> {
+    let extents = if extend.left > extend.right {
+        vec![
+            BoundingBox::new(bounds.left, extend.bottom, extend.right, extend.top),
+            BoundingBox::new(extend.left, extend.bottom, bounds.right, extend.top),
+        ]
+    } else {
+        vec![*extend]
+    };
+    extents
+        .into_iter()
+        .map(|bbox| {
+            BoundingBox::new(
+                bbox.left.max(bounds.left),
+                bbox.bottom.max(bounds.bottom),
+                bbox.right.min(bounds.right),
+                bbox.top.min(bounds.top),
+            )
+        })
+        .collect()
+}
+
 fn transformed_bbox(extent: &[f64], crs: &Crs, extent_crs: Option<&Crs>) -> Result<BoundingBox> {
     let (mut left, mut bottom, mut right, mut top) = (extent[0], extent[1], extent[2], extent[3]);
     if let Some(extent_crs) = extent_crs {
         if extent_crs != crs {
             let transform = Transformer::from_crs(extent_crs, crs, true)?;
-            (left, bottom, right, top) =
-                transform.transform_bounds(left, bottom, right, top /* Some(21) */)?;
+            (left, bottom, right, top) = transform.transform_bounds(left, bottom, right, top)?;
         }
     }
     Ok(BoundingBox::new(left, bottom, right, top))
@@ -1096,23 +1432,12 @@ fn transformed_bbox(extent: &[f64], crs: &Crs, extent_crs: Option<&Crs>) -> Resu
 //     If the CRS uses meters as units of measure for the horizontal dimensions,
 //     then metersPerUnit=1; if it has degrees, then metersPerUnit=2pa/360
 //     (a is the Earth maximum radius of the ellipsoid).
-pub fn meters_per_unit(crs: &Crs) -> f64 {
-    const SEMI_MAJOR_METRE: f64 = 6378137.0; /* crs.ellipsoid.semi_major_metre */
-    let unit_name = if crs.as_srid() == 4326 {
-        "degree" // FIXME: crs.axis_info[0].unit_name;
-    } else {
-        "metre"
-    };
-    match unit_name {
-        "metre" => 1.0,
-        "degree" => 2.0 * PI * SEMI_MAJOR_METRE / 360.0,
-        "foot" => 0.3048,
-        "US survey foot" => 0.30480060960121924,
-        _ => panic!(
-            "CRS {crs:?} with Unit Name `{}` is not supported",
-            unit_name
-        ),
-    }
+///
+/// Fails with [`TmsError::UnknownCrsUnits`] for CRS which are not known.
+pub fn meters_per_unit(crs: &Crs) -> Result<f64> {
+    crs_info(crs)
+        .map(|info| info.meters_per_unit())
+        .ok_or_else(|| TmsError::UnknownCrsUnits(crs.to_urn()))
 }
 
 const LL_EPSILON: f64 = 1e-11;
@@ -1125,10 +1450,16 @@ pub fn point_in_bbox(point: Coords, bbox: BoundingBox, precision: u8 /* = 5 */)
         let factor = 10.0_f64.powi(precision as i32);
         (number * factor).round() / factor
     }
-    let inside = round_to_prec(point.x, precision) >= round_to_prec(bbox.left, precision)
-        && round_to_prec(point.x, precision) <= round_to_prec(bbox.right, precision)
-        && round_to_prec(point.y, precision) >= round_to_prec(bbox.bottom, precision)
-        && round_to_prec(point.y, precision) <= round_to_prec(bbox.top, precision);
+    let rounded = BoundingBox::new(
+        round_to_prec(bbox.left, precision),
+        round_to_prec(bbox.bottom, precision),
+        round_to_prec(bbox.right, precision),
+        round_to_prec(bbox.top, precision),
+    );
+    let inside = rounded.contains(&Coords::new(
+        round_to_prec(point.x, precision),
+        round_to_prec(point.y, precision),
+    ));
     if inside {
         Ok(())
     } else {
@@ -5,48 +5,29 @@
 
 //! TMS iterators
 
-use crate::{MinMax, Xyz};
+use crate::{Curve, TileRange, Xyz};
 
 /// Level-by-level iterator
 pub struct XyzIterator {
-    z: u8,
     x: u64,
     y: u64,
-    z_min: u8,
-    z_max: u8,
-    limits: Vec<MinMax>,
-    finished: bool,
+    /// Index of current limits
+    idx: usize,
+    /// Limits ordered by zoom level
+    limits: Vec<TileRange>,
 }
 
 impl XyzIterator {
-    pub(crate) fn new(z_min: u8, z_max: u8, limits: Vec<MinMax>) -> XyzIterator {
-        // "Empty" iterator for invalid parameters
-        const EMPTY_ITER: XyzIterator = XyzIterator {
-            z: 0,
-            x: 0,
-            y: 0,
-            z_min: 0,
-            z_max: 0,
-            limits: Vec::new(),
-            finished: true,
-        };
-        if z_min <= z_max {
-            if let Some(limit) = &limits.first() {
-                let z_max = std::cmp::min(z_max, z_min + limits.len().saturating_sub(1) as u8);
-                XyzIterator {
-                    z: z_min,
-                    x: limit.x_min,
-                    y: limit.y_min,
-                    z_min,
-                    z_max,
-                    limits,
-                    finished: false,
-                }
-            } else {
-                EMPTY_ITER
-            }
-        } else {
-            EMPTY_ITER
+    pub(crate) fn new(limits: Vec<TileRange>) -> XyzIterator {
+        let (x, y) = limits
+            .first()
+            .map(|limit| (limit.x_min, limit.y_min))
+            .unwrap_or_default();
+        XyzIterator {
+            x,
+            y,
+            idx: 0,
+            limits,
         }
     }
 }
@@ -55,36 +36,223 @@ impl Iterator for XyzIterator {
     type Item = Xyz;
 
     fn next(&mut self) -> Option<Self::Item> {
-        if self.finished {
-            return None;
+        while let Some(limit) = self.limits.get(self.idx) {
+            // Skip columns covered by a coalesced tile and tiles of overlapping limits of
+            // extents crossing the antimeridian
+            let previous = &self.limits[..self.idx];
+            let current = limit
+                .visit(self.x, self.y)
+                .filter(|tile| !previous.iter().any(|other| other.contains(tile)));
+            if self.y < limit.y_max {
+                self.y += 1;
+            } else if self.x < limit.x_max {
+                self.x += 1;
+                self.y = limit.y_min;
+            } else {
+                self.idx += 1;
+                if let Some(limit) = self.limits.get(self.idx) {
+                    self.x = limit.x_min;
+                    self.y = limit.y_min;
+                }
+            }
+            if current.is_some() {
+                return current;
+            }
         }
-        let current = Xyz::new(self.x, self.y, self.z);
-        let limit = &self.limits[(self.z - self.z_min) as usize];
-        if self.y < limit.y_max {
-            self.y += 1;
-        } else if self.x < limit.x_max {
-            self.x += 1;
-            self.y = limit.y_min;
-        } else if self.z < self.z_max {
-            self.z += 1;
-            let limit = &self.limits[(self.z - self.z_min) as usize];
-            self.x = limit.x_min;
-            self.y = limit.y_min;
-        } else {
-            self.finished = true;
+        None
+    }
+}
+
+/// Action after visiting a tile in a pyramid traversal
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TileVisit {
+    /// Visit the children of the tile
+    Continue,
+    /// Don't visit the children of the tile
+    SkipChildren,
+    /// Stop the traversal
+    Stop,
+}
+
+/// Iterator visiting tiles in the order of a space-filling curve
+///
+/// Either each zoom level is traversed on its own, or all zoom levels are traversed together,
+/// visiting each tile before its children.
+pub struct CurveIterator {
+    curve: Curve,
+    /// Limits ordered by zoom level
+    limits: Vec<TileRange>,
+    /// Curve order of the tile matrix of each limit
+    orders: Vec<u8>,
+    /// Traverse all zoom levels together
+    pyramid: bool,
+    /// Index of the limits of the next zoom level
+    next_idx: usize,
+    /// Zoom level and curve order used for sorting
+    key_zoom: u8,
+    key_order: u8,
+    stack: Vec<Block>,
+}
+
+/// Square block of `2^level` x `2^level` tiles
+#[derive(Clone, Copy)]
+struct Block {
+    z: u8,
+    x: u64,
+    y: u64,
+    level: u8,
+}
+
+impl CurveIterator {
+    pub(crate) fn new(
+        curve: Curve,
+        limits: Vec<TileRange>,
+        orders: Vec<u8>,
+        pyramid: bool,
+    ) -> CurveIterator {
+        let mut iter = CurveIterator {
+            curve,
+            limits,
+            orders,
+            pyramid,
+            next_idx: 0,
+            key_zoom: 0,
+            key_order: 0,
+            stack: Vec::new(),
+        };
+        if pyramid {
+            if let (Some(first), Some(last)) = (iter.limits.first(), iter.limits.last()) {
+                iter.key_zoom = last.z;
+                iter.key_order = iter.orders[iter.orders.len() - 1];
+                iter.stack.push(Block {
+                    z: first.z,
+                    x: 0,
+                    y: 0,
+                    level: iter.orders[0],
+                });
+            }
+            iter.next_idx = iter.limits.len();
+        }
+        iter
+    }
+
+    /// Push the root block of the next zoom level.
+    fn start_next_zoom(&mut self) -> bool {
+        let Some(limit) = self.limits.get(self.next_idx) else {
+            return false;
+        };
+        let z = limit.z;
+        self.key_zoom = z;
+        self.key_order = self.orders[self.next_idx];
+        self.stack.push(Block {
+            z,
+            x: 0,
+            y: 0,
+            level: self.key_order,
+        });
+        while self
+            .limits
+            .get(self.next_idx)
+            .map_or(false, |limit| limit.z == z)
+        {
+            self.next_idx += 1;
+        }
+        true
+    }
+
+    /// Check if a block or its descendants contain tiles within the limits.
+    fn overlaps(&self, block: &Block) -> bool {
+        self.limits.iter().any(|limit| {
+            if limit.z < block.z || (!self.pyramid && limit.z > block.z) {
+                return false;
+            }
+            let shift = limit.z - block.z;
+            let size = 1u64 << block.level;
+            block.x << shift <= limit.x_max
+                && ((block.x + size) << shift) > limit.x_min
+                && block.y << shift <= limit.y_max
+                && ((block.y + size) << shift) > limit.y_min
+        })
+    }
+
+    fn visit(&self, z: u8, x: u64, y: u64) -> Option<Xyz> {
+        self.limits
+            .iter()
+            .filter(|limit| {
+                limit.z == z
+                    && (limit.x_min..=limit.x_max).contains(&x)
+                    && (limit.y_min..=limit.y_max).contains(&y)
+            })
+            .find_map(|limit| limit.visit(x, y))
+    }
+
+    /// Push blocks overlapping the limits, to be visited in curve order.
+    fn push_blocks(&mut self, blocks: [Block; 4]) {
+        let mut blocks = blocks
+            .into_iter()
+            .filter(|block| self.overlaps(block))
+            .map(|block| {
+                let shift = self.key_zoom - block.z;
+                let key = self
+                    .curve
+                    .index(self.key_order, block.x << shift, block.y << shift);
+                (key, block)
+            })
+            .collect::<Vec<_>>();
+        blocks.sort_by_key(|(key, _)| std::cmp::Reverse(*key));
+        self.stack
+            .extend(blocks.into_iter().map(|(_, block)| block));
+    }
+}
+
+impl Iterator for CurveIterator {
+    type Item = Xyz;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let Some(block) = self.stack.pop() else {
+                if self.start_next_zoom() {
+                    continue;
+                }
+                return None;
+            };
+            if block.level > 0 {
+                let level = block.level - 1;
+                let half = 1u64 << level;
+                self.push_blocks(
+                    [(0, 0), (half, 0), (0, half), (half, half)].map(|(dx, dy)| Block {
+                        z: block.z,
+                        x: block.x + dx,
+                        y: block.y + dy,
+                        level,
+                    }),
+                );
+                continue;
+            }
+            if self.pyramid && block.z < self.key_zoom {
+                self.push_blocks([(0, 0), (1, 0), (0, 1), (1, 1)].map(|(dx, dy)| Block {
+                    z: block.z + 1,
+                    x: block.x * 2 + dx,
+                    y: block.y * 2 + dy,
+                    level: 0,
+                }));
+            }
+            if let Some(tile) = self.visit(block.z, block.x, block.y) {
+                return Some(tile);
+            }
         }
-        Some(current)
     }
 }
 
 #[cfg(test)]
 mod test {
-    use crate::{tms, Xyz};
+    use crate::{tms, BoundingBox, Curve, TileVisit, TmsError, Xyz};
+    use std::collections::HashSet;
 
     #[test]
     fn test_mercator_iter() {
         let tms = tms().lookup("WebMercatorQuad").unwrap();
-        let griditer = tms.xyz_iterator(&tms.xy_bbox(), 0, 2);
+        let griditer = tms.xyz_iterator(&tms.xy_bbox(), 0, 2).unwrap();
         let cells = griditer.collect::<Vec<_>>();
         assert_eq!(
             cells,
@@ -113,7 +281,7 @@ mod test {
             ]
         );
 
-        let griditer = tms.xyz_iterator(&tms.xy_bbox(), 1, 2);
+        let griditer = tms.xyz_iterator(&tms.xy_bbox(), 1, 2).unwrap();
         let cells = griditer.collect::<Vec<_>>();
         assert_eq!(
             cells,
@@ -141,7 +309,7 @@ mod test {
             ]
         );
 
-        let griditer = tms.xyz_iterator(&tms.xy_bbox(), 0, 0);
+        let griditer = tms.xyz_iterator(&tms.xy_bbox(), 0, 0).unwrap();
         let cells = griditer.collect::<Vec<_>>();
         assert_eq!(cells, vec![Xyz::new(0, 0, 0)]);
     }
@@ -150,12 +318,248 @@ mod test {
     fn invalid_iters() {
         let tms = tms().lookup("WebMercatorQuad").unwrap();
 
-        let griditer = tms.xyz_iterator(&tms.xy_bbox(), 3, 2);
+        let griditer = tms.xyz_iterator(&tms.xy_bbox(), 3, 2).unwrap();
         assert_eq!(griditer.count(), 0);
 
         // z_min >= (z_max - z_min)
         // Did panic in earlier versions
-        let griditer = tms.xyz_iterator(&tms.xy_bbox(), 2, 3);
+        let griditer = tms.xyz_iterator(&tms.xy_bbox(), 2, 3).unwrap();
         assert_eq!(griditer.count(), 80);
     }
+
+    #[test]
+    fn antimeridian_iters() {
+        let tms = tms().lookup("WebMercatorQuad").unwrap();
+        let expected = vec![
+            Xyz::new(0, 0, 1),
+            Xyz::new(1, 0, 1),
+            Xyz::new(0, 1, 2),
+            Xyz::new(3, 1, 2),
+        ];
+
+        let extend = BoundingBox::new(15_000_000.0, 1_000_000.0, -15_000_000.0, 2_000_000.0);
+        let griditer = tms.xyz_iterator(&extend, 1, 2).unwrap();
+        assert_eq!(griditer.collect::<Vec<_>>(), expected);
+
+        let extend = BoundingBox::new(170.0, 10.0, -170.0, 15.0);
+        let griditer = tms.xyz_iterator_geographic(&extend, 1, 2).unwrap();
+        assert_eq!(griditer.collect::<Vec<_>>(), expected);
+
+        // Both halves are within the same tile at low zoom levels
+        let expected = vec![Xyz::new(0, 0, 0), Xyz::new(0, 0, 1), Xyz::new(1, 0, 1)];
+        let extend = BoundingBox::new(170.0, 5.0, -170.0, 10.0);
+        let griditer = tms.xyz_iterator_geographic(&extend, 0, 1).unwrap();
+        assert_eq!(griditer.collect::<Vec<_>>(), expected);
+        let extend = BoundingBox::new(19_000_000.0, 1.0, -19_000_000.0, 1_000_000.0);
+        let griditer = tms.xyz_iterator(&extend, 0, 1).unwrap();
+        assert_eq!(griditer.collect::<Vec<_>>(), expected);
+    }
+
+    #[test]
+    fn curve_iters() {
+        let tms = tms().lookup("WebMercatorQuad").unwrap();
+        let cells = tms
+            .xyz_iterator_curve(&tms.xy_bbox(), 0, 1, Curve::Hilbert)
+            .unwrap()
+            .collect::<Vec<_>>();
+        assert_eq!(
+            cells,
+            vec![
+                Xyz::new(0, 0, 0),
+                Xyz::new(0, 0, 1),
+                Xyz::new(0, 1, 1),
+                Xyz::new(1, 1, 1),
+                Xyz::new(1, 0, 1),
+            ]
+        );
+        let cells = tms
+            .xyz_iterator_curve(&tms.xy_bbox(), 1, 1, Curve::Morton)
+            .unwrap()
+            .collect::<Vec<_>>();
+        assert_eq!(
+            cells,
+            vec![
+                Xyz::new(0, 0, 1),
+                Xyz::new(1, 0, 1),
+                Xyz::new(0, 1, 1),
+                Xyz::new(1, 1, 1),
+            ]
+        );
+
+        // Same tiles as column-major iteration, in curve order per zoom level
+        let extents = [
+            BoundingBox::new(-1_000_000.0, 5_000_000.0, 2_000_000.0, 7_000_000.0),
+            BoundingBox::new(15_000_000.0, 1_000_000.0, -15_000_000.0, 2_000_000.0),
+        ];
+        for extend in extents {
+            for curve in [Curve::Hilbert, Curve::Morton] {
+                let cells = tms
+                    .xyz_iterator_curve(&extend, 3, 8, curve)
+                    .unwrap()
+                    .collect::<Vec<_>>();
+                let expected = tms.xyz_iterator(&extend, 3, 8).unwrap();
+                let expected = expected.collect::<HashSet<_>>();
+                assert_eq!(cells.len(), expected.len());
+                assert_eq!(cells.iter().copied().collect::<HashSet<_>>(), expected);
+                let keys = cells
+                    .iter()
+                    .map(|tile| (tile.z, curve.index(tile.z, tile.x, tile.y)))
+                    .collect::<Vec<_>>();
+                assert!(keys.windows(2).all(|pair| pair[0] < pair[1]));
+            }
+        }
+
+        // Small extent on a high zoom level
+        let extend = BoundingBox::new(1.0, 1.0, 4.0, 4.0);
+        let iter = tms.xyz_iterator_curve(&extend, 24, 24, Curve::Hilbert);
+        assert_eq!(iter.unwrap().count(), 4);
+
+        // Non-square tile matrices
+        let crs84 = crate::tms().lookup("WorldCRS84Quad").unwrap();
+        let cells = crs84
+            .xyz_iterator_curve(&crs84.xy_bbox(), 0, 0, Curve::Hilbert)
+            .unwrap()
+            .collect::<Vec<_>>();
+        assert_eq!(cells, vec![Xyz::new(0, 0, 0), Xyz::new(1, 0, 0)]);
+    }
+
+    #[test]
+    fn curve_global_iters() {
+        let tms = tms().lookup("WebMercatorQuad").unwrap();
+        let cells = tms
+            .xyz_iterator_curve_global(&tms.xy_bbox(), 0, 2, Curve::Morton)
+            .unwrap()
+            .collect::<Vec<_>>();
+        assert_eq!(cells.len(), 21);
+        assert_eq!(
+            cells[..8],
+            [
+                Xyz::new(0, 0, 0),
+                Xyz::new(0, 0, 1),
+                Xyz::new(0, 0, 2),
+                Xyz::new(1, 0, 2),
+                Xyz::new(0, 1, 2),
+                Xyz::new(1, 1, 2),
+                Xyz::new(1, 0, 1),
+                Xyz::new(2, 0, 2),
+            ]
+        );
+
+        let extend = BoundingBox::new(-1_000_000.0, 5_000_000.0, 2_000_000.0, 7_000_000.0);
+        let cells = tms
+            .xyz_iterator_curve_global(&extend, 2, 9, Curve::Hilbert)
+            .unwrap()
+            .collect::<Vec<_>>();
+        let expected = tms.xyz_iterator(&extend, 2, 9).unwrap();
+        let expected = expected.collect::<HashSet<_>>();
+        assert_eq!(cells.len(), expected.len());
+        assert_eq!(cells.iter().copied().collect::<HashSet<_>>(), expected);
+        // Ordered by position on the curve at maxzoom, parents first
+        let keys = cells
+            .iter()
+            .map(|tile| {
+                let shift = 9 - tile.z;
+                // First position of the tile's cells on the curve
+                let index = Curve::Hilbert.index(9, tile.x << shift, tile.y << shift);
+                (index >> (2 * shift) << (2 * shift), tile.z)
+            })
+            .collect::<Vec<_>>();
+        assert!(keys.windows(2).all(|pair| pair[0] < pair[1]));
+
+        let crs84 = crate::tms().lookup("WorldCRS84Quad").unwrap();
+        assert!(matches!(
+            crs84.xyz_iterator_curve_global(&crs84.xy_bbox(), 0, 2, Curve::Hilbert),
+            Err(TmsError::NoQuadkeySupport)
+        ));
+    }
+
+    #[test]
+    fn pyramid_traversal() {
+        let tms = tms().lookup("WebMercatorQuad").unwrap();
+        let mut cells = Vec::new();
+        tms.visit_pyramid(&tms.xy_bbox(), 0, 2, |tile| {
+            cells.push(*tile);
+            TileVisit::Continue
+        })
+        .unwrap();
+        assert_eq!(cells.len(), 21);
+        assert_eq!(
+            cells[..7],
+            [
+                Xyz::new(0, 0, 0),
+                Xyz::new(0, 0, 1),
+                Xyz::new(0, 0, 2),
+                Xyz::new(0, 1, 2),
+                Xyz::new(1, 0, 2),
+                Xyz::new(1, 1, 2),
+                Xyz::new(0, 1, 1),
+            ]
+        );
+
+        // Prune a subtree
+        let mut cells = Vec::new();
+        tms.visit_pyramid(&tms.xy_bbox(), 1, 3, |tile| {
+            cells.push(*tile);
+            if *tile == Xyz::new(1, 1, 1) {
+                TileVisit::SkipChildren
+            } else {
+                TileVisit::Continue
+            }
+        })
+        .unwrap();
+        assert_eq!(cells.len(), 4 + 12 + 48);
+        assert!(cells.contains(&Xyz::new(1, 1, 1)));
+        assert!(!cells.contains(&Xyz::new(2, 2, 2)));
+        assert!(!cells.contains(&Xyz::new(7, 7, 3)));
+
+        // Stop the traversal
+        let mut count = 0;
+        tms.visit_pyramid(&tms.xy_bbox(), 0, 20, |_| {
+            count += 1;
+            if count == 3 {
+                TileVisit::Stop
+            } else {
+                TileVisit::Continue
+            }
+        })
+        .unwrap();
+        assert_eq!(count, 3);
+    }
+
+    #[test]
+    fn pyramid_traversal_extent() {
+        let extend = BoundingBox::new(-1_000_000.0, 5_000_000.0, 2_000_000.0, 7_000_000.0);
+        for name in ["WebMercatorQuad", "WorldCRS84Quad", "GNOSISGlobalGrid"] {
+            let grid = tms().lookup(name).unwrap();
+            let extend = if name == "WebMercatorQuad" {
+                extend
+            } else {
+                BoundingBox::new(-10.0, 40.0, 20.0, 55.0)
+            };
+            let mut cells = Vec::new();
+            grid.visit_pyramid(&extend, 1, 5, |tile| {
+                cells.push(*tile);
+                TileVisit::Continue
+            })
+            .unwrap();
+            let expected = grid.xyz_iterator(&extend, 1, 5).unwrap();
+            let expected = expected.collect::<HashSet<_>>();
+            assert_eq!(cells.len(), expected.len(), "{name}");
+            assert_eq!(cells.into_iter().collect::<HashSet<_>>(), expected);
+        }
+    }
+
+    #[test]
+    fn pyramid_traversal_antimeridian() {
+        let mercator = tms().lookup("WebMercatorQuad").unwrap();
+        let extend = BoundingBox::new(19_000_000.0, -1_000_000.0, -19_000_000.0, 1_000_000.0);
+        let mut cells = Vec::new();
+        mercator
+            .visit_pyramid(&extend, 0, 1, |tile| {
+                cells.push(tile.to_string());
+                TileVisit::Continue
+            })
+            .unwrap();
+        assert_eq!(cells, ["0/0/0", "1/0/0", "1/0/1", "1/1/0", "1/1/1"]);
+    }
 }
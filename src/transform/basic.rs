@@ -1,51 +1,102 @@
 use crate::transform::{Error, Result, Transform};
-use ogcapi_types::common::Crs;
+use ogcapi_types::common::{Authority, Crs};
 use std::f64::consts;
 
+/// Transformer for WGS84 based CRS implemented in Rust.
+///
+/// Supported are geographic coordinates (EPSG:4326, OGC:CRS84), Web Mercator (EPSG:3857),
+/// World Mercator (EPSG:3395), UTM zones (EPSG:32601-32660, EPSG:32701-32760) and
+/// UPS North and South (EPSG:5041, EPSG:5042, EPSG:32661, EPSG:32761).
+/// Coordinates are always in (x, y) respectively (lon, lat) order.
 #[derive(Clone, Debug)]
 pub struct BasicTransformer {
-    from: Crs,
-    to: Crs,
+    from: Projection,
+    to: Projection,
+}
+
+/// Projections supported by [BasicTransformer]
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Projection {
+    Geographic,
+    WebMercator,
+    WorldMercator,
+    Utm { zone: u8, south: bool },
+    Ups { south: bool },
+}
+
+impl Projection {
+    fn from_crs(crs: &Crs) -> Option<Self> {
+        let code = match crs.authority {
+            Authority::OGC if crs.code == "CRS84" => return Some(Projection::Geographic),
+            Authority::OGC => return None,
+            Authority::EPSG => crs.code.parse::<u16>().ok()?,
+        };
+        match code {
+            4326 => Some(Projection::Geographic),
+            3857 => Some(Projection::WebMercator),
+            3395 => Some(Projection::WorldMercator),
+            32601..=32660 => Some(Projection::Utm {
+                zone: (code - 32600) as u8,
+                south: false,
+            }),
+            32701..=32760 => Some(Projection::Utm {
+                zone: (code - 32700) as u8,
+                south: true,
+            }),
+            5041 | 32661 => Some(Projection::Ups { south: false }),
+            5042 | 32761 => Some(Projection::Ups { south: true }),
+            _ => None,
+        }
+    }
+
+    /// Project geographic coordinates in degrees
+    fn forward(&self, lon: f64, lat: f64) -> (f64, f64) {
+        match *self {
+            Projection::Geographic => (lon, lat),
+            Projection::WebMercator => lonlat_to_merc(lon, lat),
+            Projection::WorldMercator => lonlat_to_world_merc(lon, lat),
+            Projection::Utm { zone, south } => lonlat_to_utm(lon, lat, zone, south),
+            Projection::Ups { south } => lonlat_to_ups(lon, lat, south),
+        }
+    }
+
+    /// Unproject coordinates to geographic coordinates in degrees
+    fn inverse(&self, x: f64, y: f64) -> (f64, f64) {
+        match *self {
+            Projection::Geographic => (x, y),
+            Projection::WebMercator => merc_to_lonlat(x, y),
+            Projection::WorldMercator => world_merc_to_lonlat(x, y),
+            Projection::Utm { zone, south } => utm_to_lonlat(x, y, zone, south),
+            Projection::Ups { south } => ups_to_lonlat(x, y, south),
+        }
+    }
 }
 
 impl Transform for BasicTransformer {
     fn from_crs(from: &Crs, to: &Crs, _always_xy: bool) -> Result<Self> {
-        match (from.as_srid(), to.as_srid()) {
-            (4326, 3857) | (3857, 4326) | (3395, 4326) | (4326, 3395) => Ok(BasicTransformer {
-                from: from.clone(),
-                to: to.clone(),
-            }),
-            (_a, _b) => Err(Error::TransformationUnsupported(from.clone(), to.clone())),
+        match (Projection::from_crs(from), Projection::from_crs(to)) {
+            (Some(from), Some(to)) => Ok(BasicTransformer { from, to }),
+            _ => Err(Error::TransformationUnsupported(from.clone(), to.clone())),
         }
     }
     fn transform(&self, x: f64, y: f64) -> Result<(f64, f64)> {
-        if self.from.as_srid() != 4326 || self.to.as_srid() != 3857 {
-            return Err(Error::TransformationUnsupported(
-                self.from.clone(),
-                self.to.clone(),
-            ));
+        if self.from == self.to {
+            return Ok((x, y));
         }
-        Ok(lonlat_to_merc(x, y))
-    }
-    fn transform_bounds(
-        &self,
-        left: f64,
-        bottom: f64,
-        right: f64,
-        top: f64,
-    ) -> Result<(f64, f64, f64, f64)> {
-        if self.from.as_srid() != 4326 || self.to.as_srid() != 3857 {
-            return Err(Error::TransformationUnsupported(
-                self.from.clone(),
-                self.to.clone(),
-            ));
-        }
-        let (minx, miny) = lonlat_to_merc(left, top);
-        let (maxx, maxy) = lonlat_to_merc(right, bottom);
-        Ok((minx, miny, maxx, maxy))
+        let (lon, lat) = self.from.inverse(x, y);
+        Ok(self.to.forward(lon, lat))
     }
 }
 
+// WGS84 ellipsoid
+const WGS84_A: f64 = 6378137.0;
+const WGS84_F: f64 = 1.0 / 298.257223563;
+
+/// First eccentricity of the WGS84 ellipsoid
+fn wgs84_e() -> f64 {
+    (WGS84_F * (2.0 - WGS84_F)).sqrt()
+}
+
 /// Returns the Spherical Mercator (x, y) in meters
 pub fn lonlat_to_merc(lon: f64, lat: f64) -> (f64, f64) {
     // from mod web_mercator in grid_test
@@ -55,6 +106,202 @@ pub fn lonlat_to_merc(lon: f64, lat: f64) -> (f64, f64) {
     (x, y)
 }
 
+/// Returns the (lon, lat) of a Spherical Mercator (x, y) in meters
+pub fn merc_to_lonlat(x: f64, y: f64) -> (f64, f64) {
+    let lon = (x / 6378137.0).to_degrees();
+    let lat = (2.0 * (y / 6378137.0).exp().atan() - consts::FRAC_PI_2).to_degrees();
+    (lon, lat)
+}
+
+/// Returns the World Mercator (ellipsoidal, EPSG:3395) (x, y) in meters
+pub fn lonlat_to_world_merc(lon: f64, lat: f64) -> (f64, f64) {
+    let e = wgs84_e();
+    let phi = lat.to_radians();
+    let esin = e * phi.sin();
+    let x = WGS84_A * lon.to_radians();
+    let y = WGS84_A
+        * ((consts::FRAC_PI_4 + 0.5 * phi).tan() * ((1.0 - esin) / (1.0 + esin)).powf(0.5 * e))
+            .ln();
+    (x, y)
+}
+
+/// Returns the (lon, lat) of a World Mercator (ellipsoidal, EPSG:3395) (x, y) in meters
+pub fn world_merc_to_lonlat(x: f64, y: f64) -> (f64, f64) {
+    let lon = (x / WGS84_A).to_degrees();
+    let lat = isometric_to_lat((-y / WGS84_A).exp());
+    (lon, lat)
+}
+
+/// Latitude in degrees from `t = tan(pi/4 - phi/2) / ((1 - e sin(phi)) / (1 + e sin(phi)))^(e/2)`
+fn isometric_to_lat(t: f64) -> f64 {
+    let e = wgs84_e();
+    let mut phi = consts::FRAC_PI_2 - 2.0 * t.atan();
+    for _ in 0..15 {
+        let esin = e * phi.sin();
+        let next =
+            consts::FRAC_PI_2 - 2.0 * (t * ((1.0 - esin) / (1.0 + esin)).powf(0.5 * e)).atan();
+        let delta = (next - phi).abs();
+        phi = next;
+        if delta < 1e-12 {
+            break;
+        }
+    }
+    phi.to_degrees()
+}
+
+// UTM parameters
+const UTM_K0: f64 = 0.9996;
+const UTM_FALSE_EASTING: f64 = 500000.0;
+const UTM_FALSE_NORTHING_SOUTH: f64 = 10000000.0;
+
+/// Coefficients of the Krüger series for the transverse Mercator projection (order n^6).
+struct KruegerSeries {
+    /// Rectifying radius
+    a: f64,
+    alpha: [f64; 6],
+    beta: [f64; 6],
+}
+
+fn krueger_series() -> KruegerSeries {
+    let n = WGS84_F / (2.0 - WGS84_F);
+    let n2 = n * n;
+    let n3 = n2 * n;
+    let n4 = n3 * n;
+    let n5 = n4 * n;
+    let n6 = n5 * n;
+    KruegerSeries {
+        a: WGS84_A / (1.0 + n) * (1.0 + n2 / 4.0 + n4 / 64.0 + n6 / 256.0),
+        alpha: [
+            n / 2.0 - 2.0 / 3.0 * n2 + 5.0 / 16.0 * n3 + 41.0 / 180.0 * n4 - 127.0 / 288.0 * n5
+                + 7891.0 / 37800.0 * n6,
+            13.0 / 48.0 * n2 - 3.0 / 5.0 * n3 + 557.0 / 1440.0 * n4 + 281.0 / 630.0 * n5
+                - 1983433.0 / 1935360.0 * n6,
+            61.0 / 240.0 * n3 - 103.0 / 140.0 * n4
+                + 15061.0 / 26880.0 * n5
+                + 167603.0 / 181440.0 * n6,
+            49561.0 / 161280.0 * n4 - 179.0 / 168.0 * n5 + 6601661.0 / 7257600.0 * n6,
+            34729.0 / 80640.0 * n5 - 3418889.0 / 1995840.0 * n6,
+            212378941.0 / 319334400.0 * n6,
+        ],
+        beta: [
+            n / 2.0 - 2.0 / 3.0 * n2 + 37.0 / 96.0 * n3 - 1.0 / 360.0 * n4 - 81.0 / 512.0 * n5
+                + 96199.0 / 604800.0 * n6,
+            n2 / 48.0 + n3 / 15.0 - 437.0 / 1440.0 * n4 + 46.0 / 105.0 * n5
+                - 1118711.0 / 3870720.0 * n6,
+            17.0 / 480.0 * n3 - 37.0 / 840.0 * n4 - 209.0 / 4480.0 * n5 + 5569.0 / 90720.0 * n6,
+            4397.0 / 161280.0 * n4 - 11.0 / 504.0 * n5 - 830251.0 / 7257600.0 * n6,
+            4583.0 / 161280.0 * n5 - 108847.0 / 3991680.0 * n6,
+            20648693.0 / 638668800.0 * n6,
+        ],
+    }
+}
+
+fn utm_central_meridian(zone: u8) -> f64 {
+    f64::from(zone) * 6.0 - 183.0
+}
+
+/// Returns the UTM (easting, northing) in meters
+pub fn lonlat_to_utm(lon: f64, lat: f64, zone: u8, south: bool) -> (f64, f64) {
+    let e = wgs84_e();
+    let series = krueger_series();
+    let phi = lat.to_radians();
+    let lambda = (lon - utm_central_meridian(zone)).to_radians();
+    // Conformal latitude
+    let t = (phi.sin().atanh() - e * (e * phi.sin()).atanh()).sinh();
+    let xi_ = t.atan2(lambda.cos());
+    let eta_ = (lambda.sin() / (1.0 + t * t).sqrt()).atanh();
+    let (mut xi, mut eta) = (xi_, eta_);
+    for (j, alpha) in series.alpha.iter().enumerate() {
+        let k = 2.0 * (j + 1) as f64;
+        xi += alpha * (k * xi_).sin() * (k * eta_).cosh();
+        eta += alpha * (k * xi_).cos() * (k * eta_).sinh();
+    }
+    let false_northing = if south { UTM_FALSE_NORTHING_SOUTH } else { 0.0 };
+    (
+        UTM_FALSE_EASTING + UTM_K0 * series.a * eta,
+        false_northing + UTM_K0 * series.a * xi,
+    )
+}
+
+/// Returns the (lon, lat) of UTM (easting, northing) in meters
+pub fn utm_to_lonlat(x: f64, y: f64, zone: u8, south: bool) -> (f64, f64) {
+    let e = wgs84_e();
+    let series = krueger_series();
+    let false_northing = if south { UTM_FALSE_NORTHING_SOUTH } else { 0.0 };
+    let xi = (y - false_northing) / (UTM_K0 * series.a);
+    let eta = (x - UTM_FALSE_EASTING) / (UTM_K0 * series.a);
+    let (mut xi_, mut eta_) = (xi, eta);
+    for (j, beta) in series.beta.iter().enumerate() {
+        let k = 2.0 * (j + 1) as f64;
+        xi_ -= beta * (k * xi).sin() * (k * eta).cosh();
+        eta_ -= beta * (k * xi).cos() * (k * eta).sinh();
+    }
+    let lambda = eta_.sinh().atan2(xi_.cos());
+    // Tangent of conformal latitude
+    let tau_ = xi_.sin() / (eta_.sinh().powi(2) + xi_.cos().powi(2)).sqrt();
+    // Newton iteration for tangent of latitude (Karney 2011)
+    let e2 = e * e;
+    let mut tau = tau_;
+    for _ in 0..15 {
+        let sigma = (e * (e * tau / (1.0 + tau * tau).sqrt()).atanh()).sinh();
+        let tau_i = tau * (1.0 + sigma * sigma).sqrt() - sigma * (1.0 + tau * tau).sqrt();
+        let delta = (tau_ - tau_i) / (1.0 + tau_i * tau_i).sqrt() * (1.0 + (1.0 - e2) * tau * tau)
+            / ((1.0 - e2) * (1.0 + tau * tau).sqrt());
+        tau += delta;
+        if delta.abs() < 1e-12 {
+            break;
+        }
+    }
+    (
+        utm_central_meridian(zone) + lambda.to_degrees(),
+        tau.atan().to_degrees(),
+    )
+}
+
+// UPS parameters
+const UPS_K0: f64 = 0.994;
+const UPS_FALSE_EASTING: f64 = 2000000.0;
+const UPS_FALSE_NORTHING: f64 = 2000000.0;
+
+/// Factor of polar stereographic radius: `2 a k0 / sqrt((1+e)^(1+e) (1-e)^(1-e))`
+fn ups_rho_factor() -> f64 {
+    let e = wgs84_e();
+    2.0 * WGS84_A * UPS_K0 / ((1.0 + e).powf(1.0 + e) * (1.0 - e).powf(1.0 - e)).sqrt()
+}
+
+/// Returns the UPS North or South (easting, northing) in meters
+pub fn lonlat_to_ups(lon: f64, lat: f64, south: bool) -> (f64, f64) {
+    let e = wgs84_e();
+    // Project the south pole like the north pole with mirrored latitude
+    let phi = if south { -lat } else { lat }.to_radians();
+    let esin = e * phi.sin();
+    let t = (consts::FRAC_PI_4 - 0.5 * phi).tan() / ((1.0 - esin) / (1.0 + esin)).powf(0.5 * e);
+    let rho = ups_rho_factor() * t;
+    let lambda = lon.to_radians();
+    let dy = rho * lambda.cos();
+    (
+        UPS_FALSE_EASTING + rho * lambda.sin(),
+        if south {
+            UPS_FALSE_NORTHING + dy
+        } else {
+            UPS_FALSE_NORTHING - dy
+        },
+    )
+}
+
+/// Returns the (lon, lat) of UPS North or South (easting, northing) in meters
+pub fn ups_to_lonlat(x: f64, y: f64, south: bool) -> (f64, f64) {
+    let dx = x - UPS_FALSE_EASTING;
+    let dy = y - UPS_FALSE_NORTHING;
+    let rho = dx.hypot(dy);
+    let lat = isometric_to_lat(rho / ups_rho_factor());
+    if south {
+        (dx.atan2(dy).to_degrees(), -lat)
+    } else {
+        (dx.atan2(-dy).to_degrees(), lat)
+    }
+}
+
 /// Returns the upper left (lon, lat) of a tile
 pub(crate) fn merc_tile_ul(xtile: u32, ytile: u32, zoom: u8) -> (f64, f64) {
     let n = (zoom as f64).exp2();
@@ -63,3 +310,96 @@ pub(crate) fn merc_tile_ul(xtile: u32, ytile: u32, zoom: u8) -> (f64, f64) {
     let lat_deg = lat_rad.to_degrees();
     (lon_deg, lat_deg)
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn assert_close(a: (f64, f64), b: (f64, f64), eps: f64) {
+        assert!(
+            (a.0 - b.0).abs() < eps && (a.1 - b.1).abs() < eps,
+            "{a:?} != {b:?}"
+        );
+    }
+
+    #[test]
+    fn web_mercator() {
+        assert_close(
+            lonlat_to_merc(159.31, -42.0),
+            (17734308.078276414, -5160979.444049783),
+            1e-6,
+        );
+        assert_close(
+            merc_to_lonlat(17734308.078276414, -5160979.444049783),
+            (159.31, -42.0),
+            1e-9,
+        );
+    }
+
+    #[test]
+    fn world_mercator() {
+        // Latitude limit of WorldMercatorWGS84Quad
+        assert_close(
+            world_merc_to_lonlat(-20037508.3427892, 20037508.3427892),
+            (-180.0, 85.0840590501),
+            1e-9,
+        );
+        let (x, y) = lonlat_to_world_merc(10.0, 50.0);
+        assert_close(world_merc_to_lonlat(x, y), (10.0, 50.0), 1e-9);
+        assert!(y < lonlat_to_merc(10.0, 50.0).1);
+    }
+
+    #[test]
+    fn utm() {
+        assert_close(lonlat_to_utm(3.0, 0.0, 31, false), (500000.0, 0.0), 1e-6);
+        // CN Tower: 17T 630084 4833438
+        let (lon, lat) = (-79.38713888888889, 43.64256666666667);
+        let (x, y) = lonlat_to_utm(lon, lat, 17, false);
+        assert_close((x.floor(), y.floor()), (630084.0, 4833438.0), 1e-6);
+        assert_close(utm_to_lonlat(x, y, 17, false), (lon, lat), 1e-9);
+        let (x, y) = lonlat_to_utm(151.2, -33.9, 56, true);
+        assert!(y < UTM_FALSE_NORTHING_SOUTH);
+        assert_close(utm_to_lonlat(x, y, 56, true), (151.2, -33.9), 1e-9);
+    }
+
+    #[test]
+    fn ups() {
+        assert_close(
+            lonlat_to_ups(0.0, 90.0, false),
+            (2000000.0, 2000000.0),
+            1e-6,
+        );
+        assert_close(
+            lonlat_to_ups(0.0, -90.0, true),
+            (2000000.0, 2000000.0),
+            1e-6,
+        );
+        // Example of EPSG Guidance Note 7-2 (Polar Stereographic variant A)
+        assert_close(
+            lonlat_to_ups(44.0, 73.0, false),
+            (3320416.75, 632668.43),
+            0.01,
+        );
+        assert_close(
+            ups_to_lonlat(3320416.75, 632668.43, false),
+            (44.0, 73.0),
+            1e-6,
+        );
+        let (x, y) = lonlat_to_ups(-120.0, -75.0, true);
+        assert_close(ups_to_lonlat(x, y, true), (-120.0, -75.0), 1e-9);
+    }
+
+    #[test]
+    fn transform_crs() {
+        let utm31 = Crs::from_epsg(32631);
+        let t = BasicTransformer::from_crs(&Crs::default(), &utm31, true).unwrap();
+        assert_close(t.transform(3.0, 0.0).unwrap(), (500000.0, 0.0), 1e-6);
+        let t = BasicTransformer::from_crs(&utm31, &Crs::from_epsg(3857), true).unwrap();
+        assert_close(
+            t.transform(500000.0, 0.0).unwrap(),
+            lonlat_to_merc(3.0, 0.0),
+            1e-6,
+        );
+        assert!(BasicTransformer::from_crs(&Crs::default(), &Crs::from_epsg(3035), true).is_err());
+    }
+}
@@ -2,27 +2,99 @@ mod basic;
 #[cfg(feature = "projtransform")]
 mod proj;
 
+use crate::crs::crs_info;
 pub use basic::*;
 use core::fmt::Debug;
 use ogcapi_types::common::Crs;
+use once_cell::sync::Lazy;
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::sync::{Mutex, PoisonError};
 
 #[cfg(feature = "projtransform")]
 pub type Transformer = crate::transform::proj::ProjTransformer;
 #[cfg(not(feature = "projtransform"))]
 pub type Transformer = BasicTransformer;
 
+/// Number of points added to each edge when transforming bounds
+pub const DENSIFY_PTS: usize = 21;
+
 /// Basic set of coordinate transformation operations
 pub trait Transform: Sized + Debug {
     fn from_crs(from: &Crs, to: &Crs, always_xy: bool /* =true */) -> Result<Self>;
     fn transform(&self, x: f64, y: f64) -> Result<(f64, f64)>;
+    /// Transform bounds with [DENSIFY_PTS] points added to each edge.
     fn transform_bounds(
         &self,
         left: f64,
         bottom: f64,
         right: f64,
         top: f64,
-        // densify_pts=21,
-    ) -> Result<(f64, f64, f64, f64)>;
+    ) -> Result<(f64, f64, f64, f64)> {
+        self.transform_bounds_densify(left, bottom, right, top, DENSIFY_PTS)
+    }
+    /// Transform bounds and return the minimum and maximum of the transformed edges.
+    ///
+    /// `densify_pts` points are added to each edge between the corners, to follow
+    /// curved edges in the target CRS (like `proj_trans_bounds`).
+    fn transform_bounds_densify(
+        &self,
+        left: f64,
+        bottom: f64,
+        right: f64,
+        top: f64,
+        densify_pts: usize,
+    ) -> Result<(f64, f64, f64, f64)> {
+        densified_bounds(
+            |x, y| self.transform(x, y),
+            (left, bottom, right, top),
+            densify_pts,
+        )
+    }
+}
+
+/// Transform the densified edges of bounds and return their extent.
+///
+/// Points which can't be transformed are ignored, as long as at least one point succeeds.
+fn densified_bounds(
+    transform: impl Fn(f64, f64) -> Result<(f64, f64)>,
+    (left, bottom, right, top): (f64, f64, f64, f64),
+    densify_pts: usize,
+) -> Result<(f64, f64, f64, f64)> {
+    let segments = densify_pts + 1;
+    let mut extent: Option<(f64, f64, f64, f64)> = None;
+    let mut error = None;
+    for i in 0..segments {
+        let fx = left + (right - left) * i as f64 / segments as f64;
+        let fy = bottom + (top - bottom) * i as f64 / segments as f64;
+        let rx = right - (right - left) * i as f64 / segments as f64;
+        let ry = top - (top - bottom) * i as f64 / segments as f64;
+        // Walk around the bounds counter-clockwise, starting at each corner
+        for (x, y) in [(fx, bottom), (right, fy), (rx, top), (left, ry)] {
+            match transform(x, y) {
+                Ok((x, y)) if x.is_finite() && y.is_finite() => {
+                    extent = Some(match extent {
+                        Some((minx, miny, maxx, maxy)) => {
+                            (minx.min(x), miny.min(y), maxx.max(x), maxy.max(y))
+                        }
+                        None => (x, y, x, y),
+                    });
+                }
+                Ok(_) => {}
+                Err(e) => error = Some(e),
+            }
+        }
+    }
+    extent.ok_or_else(|| {
+        error.unwrap_or_else(|| {
+            Error::TransformationError("No transformable point in bounds".to_string())
+        })
+    })
+}
+
+/// Check for a geographic CRS with angular units.
+pub(crate) fn is_geographic(crs: &Crs) -> bool {
+    crs_info(crs).map_or(false, |info| info.angular_unit)
 }
 
 #[derive(thiserror::Error, Debug)]
@@ -34,3 +106,150 @@ pub enum Error {
 }
 
 pub type Result<T> = std::result::Result<T, Error>;
+
+/// Transformer handle which can be shared between threads.
+///
+/// The underlying transformer is created once per thread and CRS pair and cached for
+/// later use, since `Proj` is neither `Send` nor cheap to create.
+#[derive(Clone, Debug, PartialEq)]
+pub struct SharedTransformer {
+    from: Crs,
+    to: Crs,
+    /// Cache key of the CRS pair
+    key: usize,
+    /// Cache key of the inverse CRS pair, if `to` is geographic
+    inverse_key: Option<usize>,
+}
+
+thread_local! {
+    /// Transformers of the current thread, indexed by cache key
+    ///
+    /// Transformers are kept for the lifetime of the thread. The cache grows with the number of
+    /// distinct CRS pairs used, which are usually the CRS of the tile matrix sets and WGS 84.
+    static TRANSFORMERS: RefCell<Vec<Option<Transformer>>> = const { RefCell::new(Vec::new()) };
+}
+
+/// Cache key of a CRS pair, assigned once per pair.
+fn transformer_key(from: &Crs, to: &Crs) -> usize {
+    static KEYS: Lazy<Mutex<HashMap<(Crs, Crs), usize>>> = Lazy::new(Default::default);
+    let mut keys = KEYS.lock().unwrap_or_else(PoisonError::into_inner);
+    let next_key = keys.len();
+    *keys.entry((from.clone(), to.clone())).or_insert(next_key)
+}
+
+impl SharedTransformer {
+    /// Call `f` with the transformer of the current thread.
+    fn with<T>(&self, f: impl FnOnce(&Transformer) -> Result<T>) -> Result<T> {
+        with_transformer(self.key, &self.from, &self.to, f)
+    }
+}
+
+fn with_transformer<T>(
+    key: usize,
+    from: &Crs,
+    to: &Crs,
+    f: impl FnOnce(&Transformer) -> Result<T>,
+) -> Result<T> {
+    TRANSFORMERS.with(|transformers| {
+        let mut transformers = transformers.borrow_mut();
+        if transformers.len() <= key {
+            transformers.resize_with(key + 1, || None);
+        }
+        let transformer = match &mut transformers[key] {
+            Some(transformer) => transformer,
+            entry => entry.insert(Transformer::from_crs(from, to, true)?),
+        };
+        f(transformer)
+    })
+}
+
+impl Transform for SharedTransformer {
+    fn from_crs(from: &Crs, to: &Crs, _always_xy: bool) -> Result<Self> {
+        let transformer = SharedTransformer {
+            from: from.clone(),
+            to: to.clone(),
+            key: transformer_key(from, to),
+            inverse_key: is_geographic(to).then(|| transformer_key(to, from)),
+        };
+        // Check if transformation is supported
+        transformer.with(|_| Ok(()))?;
+        Ok(transformer)
+    }
+    fn transform(&self, x: f64, y: f64) -> Result<(f64, f64)> {
+        self.with(|t| t.transform(x, y))
+    }
+    fn transform_bounds_densify(
+        &self,
+        left: f64,
+        bottom: f64,
+        right: f64,
+        top: f64,
+        densify_pts: usize,
+    ) -> Result<(f64, f64, f64, f64)> {
+        let (mut minx, mut miny, mut maxx, mut maxy) =
+            self.with(|t| t.transform_bounds_densify(left, bottom, right, top, densify_pts))?;
+        if let Some(inverse_key) = self.inverse_key {
+            // A pole inside the bounds is not reached by the edges
+            let contains = |lat: f64| {
+                matches!(
+                    with_transformer(inverse_key, &self.to, &self.from, |t| t.transform(0.0, lat)),
+                    Ok((x, y)) if left <= x && x <= right && bottom <= y && y <= top
+                )
+            };
+            if contains(90.0) {
+                (minx, maxx, maxy) = (-180.0, 180.0, 90.0);
+            }
+            if contains(-90.0) {
+                (minx, maxx, miny) = (-180.0, 180.0, -90.0);
+            }
+        }
+        Ok((minx, miny, maxx, maxy))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn densify_curved_edges() {
+        // Corners are mapped to y <= 0, the middle of the top edge to y = 1
+        let bounds = densified_bounds(|x, y| Ok((x, y - x * x)), (-1.0, 0.0, 1.0, 1.0), 21);
+        assert_eq!(bounds.unwrap(), (-1.0, -1.0, 1.0, 1.0));
+
+        let bounds = densified_bounds(|x, y| Ok((x, y - x * x)), (-1.0, 0.0, 1.0, 1.0), 0);
+        assert_eq!(bounds.unwrap(), (-1.0, -1.0, 1.0, 0.0));
+    }
+
+    #[test]
+    fn densify_failing_points() {
+        let bounds = densified_bounds(
+            |x, y| {
+                if x < 0.0 {
+                    Err(Error::TransformationError("out of domain".to_string()))
+                } else {
+                    Ok((x, y))
+                }
+            },
+            (-1.0, 0.0, 1.0, 1.0),
+            21,
+        );
+        assert_eq!(bounds.unwrap(), (0.0, 0.0, 1.0, 1.0));
+
+        let bounds = densified_bounds(
+            |_, _| Err(Error::TransformationError("out of domain".to_string())),
+            (-1.0, 0.0, 1.0, 1.0),
+            21,
+        );
+        assert!(bounds.is_err());
+    }
+
+    #[test]
+    fn geographic_crs() {
+        assert!(is_geographic(&Crs::default()));
+        assert!(is_geographic(&Crs::from_epsg(4326)));
+        assert!(is_geographic(&Crs::from_epsg(4277)));
+        assert!(!is_geographic(&Crs::from_epsg(3857)));
+        assert!(!is_geographic(&Crs::from_epsg(2263)));
+    }
+}
@@ -12,17 +12,6 @@ impl Transform for Proj {
     fn transform(&self, x: f64, y: f64) -> Result<(f64, f64)> {
         Ok(self.convert((x, y))?)
     }
-    fn transform_bounds(
-        &self,
-        left: f64,
-        bottom: f64,
-        right: f64,
-        top: f64,
-    ) -> Result<(f64, f64, f64, f64)> {
-        let (left, bottom) = self.convert((left, bottom))?;
-        let (right, top) = self.convert((right, top))?;
-        Ok((left, bottom, right, top))
-    }
 }
 
 impl From<proj::ProjError> for Error {
@@ -0,0 +1,263 @@
+use crate::tile::{BoundingBox, Xyz};
+use crate::tms::{bottom_left_origin, Result, Tms, TmsError};
+use crate::transform::lonlat_to_merc;
+use ogcapi_types::common::Crs;
+
+/// Tile URL template.
+///
+/// Supported placeholders:
+/// * `{z}`, `{x}`, `{y}` - XYZ tile index
+/// * `{-y}` - Row counted from the bottom (TMS scheme)
+/// * `{quadkey}` - Quadkey of the tile
+/// * `{bbox}` - Tile bounds in TMS CRS as `left,bottom,right,top`
+/// * `{bbox-epsg-3857}` - Tile bounds in Web Mercator
+/// * `{TileMatrixSet}`, `{TileMatrix}`, `{TileRow}`, `{TileCol}` - WMTS placeholders. Rows are
+///   counted from the top like in the WMTS capabilities.
+/// * `{tileMatrixSetId}`, `{tileMatrix}`, `{tileRow}`, `{tileCol}` - OGC API Tiles placeholders.
+///   Rows are counted from the corner of origin of the tile matrix.
+///
+/// ```rust
+/// use tile_grid::{tms, TileUrlTemplate, Xyz};
+///
+/// let tms = tms().lookup("WebMercatorQuad").unwrap();
+/// let template = TileUrlTemplate::new("https://example.com/{z}/{x}/{y}.pbf").unwrap();
+/// let url = template.expand(&tms, &Xyz::new(1, 2, 3)).unwrap();
+/// assert_eq!(url, "https://example.com/3/1/2.pbf");
+/// assert_eq!(template.parse(&tms, &url).unwrap(), Xyz::new(1, 2, 3));
+/// ```
+#[derive(Clone, Debug)]
+pub struct TileUrlTemplate {
+    template: String,
+    parts: Vec<Part>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+enum Part {
+    Literal(String),
+    Z,
+    X,
+    Y,
+    FlippedY,
+    /// WMTS row, counted from the top
+    WmtsRow,
+    Quadkey,
+    Bbox,
+    BboxWebMercator,
+    TileMatrixSet,
+    TileMatrix,
+}
+
+impl TileUrlTemplate {
+    /// Parse a template string.
+    pub fn new(template: &str) -> Result<Self> {
+        let mut parts = Vec::new();
+        let mut rest = template;
+        while let Some(start) = rest.find('{') {
+            if start > 0 {
+                parts.push(Part::Literal(rest[..start].to_string()));
+            }
+            let end = rest[start..]
+                .find('}')
+                .ok_or_else(|| template_error(format!("unclosed placeholder in `{template}`")))?;
+            let part = match &rest[start + 1..start + end] {
+                "z" => Part::Z,
+                "x" | "TileCol" | "tileCol" => Part::X,
+                "y" | "tileRow" => Part::Y,
+                "-y" => Part::FlippedY,
+                "TileRow" => Part::WmtsRow,
+                "quadkey" => Part::Quadkey,
+                "bbox" => Part::Bbox,
+                "bbox-epsg-3857" => Part::BboxWebMercator,
+                "TileMatrixSet" | "tileMatrixSetId" => Part::TileMatrixSet,
+                "TileMatrix" | "tileMatrix" => Part::TileMatrix,
+                name => return Err(template_error(format!("unknown placeholder `{{{name}}}`"))),
+            };
+            parts.push(part);
+            rest = &rest[start + end + 1..];
+        }
+        if !rest.is_empty() {
+            parts.push(Part::Literal(rest.to_string()));
+        }
+        Ok(TileUrlTemplate {
+            template: template.to_string(),
+            parts,
+        })
+    }
+
+    /// Template string.
+    pub fn template(&self) -> &str {
+        &self.template
+    }
+
+    /// URL of a tile.
+    pub fn expand(&self, tms: &Tms, tile: &Xyz) -> Result<String> {
+        let mut url = String::with_capacity(self.template.len());
+        for part in &self.parts {
+            match part {
+                Part::Literal(text) => url.push_str(text),
+                Part::Z => url.push_str(&tile.z.to_string()),
+                Part::X => url.push_str(&tile.x.to_string()),
+                Part::Y => url.push_str(&tile.y.to_string()),
+                Part::FlippedY => url.push_str(&flip_row(tms, tile.z, tile.y)?.to_string()),
+                Part::WmtsRow => url.push_str(&wmts_row(tms, tile.z, tile.y)?.to_string()),
+                Part::Quadkey => url.push_str(&tms.quadkey(tile)?),
+                Part::Bbox => url.push_str(&tms.xy_bounds(tile)?.to_string()),
+                Part::BboxWebMercator => url.push_str(&web_mercator_bounds(tms, tile)?.to_string()),
+                Part::TileMatrixSet => url.push_str(&tms.tms.id),
+                Part::TileMatrix => url.push_str(&tms.matrix(tile.z)?.as_ref().id),
+            }
+        }
+        Ok(url)
+    }
+
+    /// Tile of a URL matching the template.
+    ///
+    /// Returns an error if the URL doesn't match the template or the tile is not valid in `tms`.
+    pub fn parse(&self, tms: &Tms, url: &str) -> Result<Xyz> {
+        let mismatch = || template_error(format!("`{url}` doesn't match `{}`", self.template));
+        let mut z = None;
+        let mut x = None;
+        let mut y = None;
+        let mut flipped_y = None;
+        let mut wmts_row = None;
+        let mut rest = url;
+        for (idx, part) in self.parts.iter().enumerate() {
+            if let Part::Literal(text) = part {
+                rest = rest.strip_prefix(text.as_str()).ok_or_else(mismatch)?;
+                continue;
+            }
+            // A placeholder extends to the following literal
+            let value = match self.parts.get(idx + 1) {
+                None => rest,
+                Some(Part::Literal(text)) => {
+                    &rest[..rest.find(text.as_str()).ok_or_else(mismatch)?]
+                }
+                Some(_) => {
+                    return Err(template_error(format!(
+                        "adjacent placeholders in `{}`",
+                        self.template
+                    )))
+                }
+            };
+            rest = &rest[value.len()..];
+            let zoom = match part {
+                Part::Z => Some(value.parse().map_err(|_| mismatch())?),
+                Part::X => {
+                    set_once(&mut x, value.parse().map_err(|_| mismatch())?, mismatch)?;
+                    None
+                }
+                Part::Y => {
+                    set_once(&mut y, value.parse().map_err(|_| mismatch())?, mismatch)?;
+                    None
+                }
+                Part::FlippedY => {
+                    set_once(
+                        &mut flipped_y,
+                        value.parse().map_err(|_| mismatch())?,
+                        mismatch,
+                    )?;
+                    None
+                }
+                Part::WmtsRow => {
+                    set_once(
+                        &mut wmts_row,
+                        value.parse().map_err(|_| mismatch())?,
+                        mismatch,
+                    )?;
+                    None
+                }
+                Part::Quadkey => {
+                    let tile = tms.quadkey_to_tile(value)?;
+                    set_once(&mut x, tile.x, mismatch)?;
+                    set_once(&mut y, tile.y, mismatch)?;
+                    Some(tile.z)
+                }
+                Part::TileMatrixSet => {
+                    if value != tms.tms.id {
+                        return Err(mismatch());
+                    }
+                    None
+                }
+                Part::TileMatrix => Some(tms.zoom_by_id(value).ok_or_else(mismatch)?),
+                Part::Bbox | Part::BboxWebMercator => {
+                    return Err(template_error(format!(
+                        "can't parse bbox in `{}`",
+                        self.template
+                    )))
+                }
+                Part::Literal(_) => None,
+            };
+            if let Some(zoom) = zoom {
+                set_once(&mut z, zoom, mismatch)?;
+            }
+        }
+        if !rest.is_empty() {
+            return Err(mismatch());
+        }
+        let z = z.ok_or_else(|| template_error(format!("no zoom level in `{}`", self.template)))?;
+        if let Some(flipped_y) = flipped_y {
+            set_once(&mut y, flip_row(tms, z, flipped_y)?, mismatch)?;
+        }
+        if let Some(wmts_row) = wmts_row {
+            set_once(&mut y, self::wmts_row(tms, z, wmts_row)?, mismatch)?;
+        }
+        let (Some(x), Some(y)) = (x, y) else {
+            return Err(template_error(format!(
+                "no tile column or row in `{}`",
+                self.template
+            )));
+        };
+        let tile = Xyz::new(x, y, z);
+        if !tms.is_valid(&tile) {
+            return Err(template_error(format!("invalid tile {tile} in `{url}`")));
+        }
+        Ok(tile)
+    }
+}
+
+/// Set a value which may be given by multiple placeholders.
+fn set_once<T: PartialEq>(
+    slot: &mut Option<T>,
+    value: T,
+    mismatch: impl Fn() -> TmsError,
+) -> Result<()> {
+    match slot {
+        Some(existing) if *existing != value => Err(mismatch()),
+        _ => {
+            *slot = Some(value);
+            Ok(())
+        }
+    }
+}
+
+/// Row counted from the other side of the tile matrix.
+fn flip_row(tms: &Tms, zoom: u8, row: u64) -> Result<u64> {
+    let matrix = tms.matrix(zoom)?;
+    u64::from(matrix.as_ref().matrix_height)
+        .checked_sub(row)
+        .and_then(|rows| rows.checked_sub(1))
+        .ok_or_else(|| template_error(format!("row {row} outside of tile matrix")))
+}
+
+/// Convert between a row of the tile matrix and a WMTS row counted from the top.
+fn wmts_row(tms: &Tms, zoom: u8, row: u64) -> Result<u64> {
+    if bottom_left_origin(tms.matrix(zoom)?.as_ref()) {
+        flip_row(tms, zoom, row)
+    } else {
+        Ok(row)
+    }
+}
+
+fn web_mercator_bounds(tms: &Tms, tile: &Xyz) -> Result<BoundingBox> {
+    if *tms.crs() == Crs::from_epsg(3857) {
+        return tms.xy_bounds(tile);
+    }
+    let bounds = tms.bounds(tile)?;
+    let (left, bottom) = lonlat_to_merc(bounds.left, bounds.bottom);
+    let (right, top) = lonlat_to_merc(bounds.right, bounds.top);
+    Ok(BoundingBox::new(left, bottom, right, top))
+}
+
+fn template_error(msg: String) -> TmsError {
+    TmsError::UrlTemplateError(msg)
+}
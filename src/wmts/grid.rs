@@ -5,8 +5,15 @@
 
 //!Tile grids
 
+use crate::crs::crs_info;
+use crate::tms::{bottom_left_origin, meters_per_unit, Tms, TmsError};
 pub use crate::transform::lonlat_to_merc;
+use ogcapi_types::common::{Authority, Crs};
+use ogcapi_types::tiles::{
+    BoundingBox2D, CornerOfOrigin, TileMatrix, TileMatrixSet, TitleDescriptionKeywords,
+};
 use std::f64::consts;
+use std::num::{NonZeroU16, NonZeroU64};
 
 /// Geographic extent
 #[derive(PartialEq, Clone, Debug)]
@@ -309,6 +316,153 @@ impl Grid {
     }
 }
 
+/// TileMatrixSet with the extent, resolutions and origin of a grid.
+///
+/// The identifier of the TileMatrixSet is `Grid<srid>`.
+impl TryFrom<&Grid> for TileMatrixSet {
+    type Error = TmsError;
+    fn try_from(grid: &Grid) -> Result<Self, Self::Error> {
+        let crs = Crs::from_epsg(grid.srid);
+        let is_inverted = crs_info(&crs).map_or(false, |info| info.axis_inverted);
+        let axis_ordered = |x: f64, y: f64| if is_inverted { [y, x] } else { [x, y] };
+        let (origin_y, corner_of_origin) = match grid.origin {
+            Origin::TopLeft => (grid.extent.maxy, None),
+            Origin::BottomLeft => (grid.extent.miny, Some(CornerOfOrigin::BottomLeft)),
+        };
+        let mpu = meters_per_unit(&crs)?;
+        let mut tile_matrices = Vec::with_capacity(grid.resolutions.len());
+        for (zoom, res) in grid.resolutions.iter().enumerate() {
+            let (maxx, maxy) = grid.level_max[zoom];
+            tile_matrices.push(TileMatrix {
+                title_description_keywords: TitleDescriptionKeywords {
+                    title: None,
+                    description: None,
+                    keywords: None,
+                },
+                id: zoom.to_string(),
+                scale_denominator: res * mpu / 0.00028,
+                cell_size: *res,
+                corner_of_origin: corner_of_origin.clone(),
+                point_of_origin: axis_ordered(grid.extent.minx, origin_y),
+                tile_width: NonZeroU16::new(grid.width).ok_or(TmsError::NonZeroError)?,
+                tile_height: NonZeroU16::new(grid.height).ok_or(TmsError::NonZeroError)?,
+                matrix_width: NonZeroU64::new(maxx.into()).ok_or(TmsError::NonZeroError)?,
+                matrix_height: NonZeroU64::new(maxy.into()).ok_or(TmsError::NonZeroError)?,
+                variable_matrix_widths: None,
+            });
+        }
+        Ok(TileMatrixSet {
+            title_description_keywords: TitleDescriptionKeywords {
+                title: Some(format!("Grid EPSG:{}", grid.srid)),
+                description: None,
+                keywords: None,
+            },
+            id: format!("Grid{}", grid.srid),
+            uri: None,
+            crs: crs.clone(),
+            ordered_axes: None,
+            well_known_scale_set: None,
+            bounding_box: Some(BoundingBox2D {
+                lower_left: axis_ordered(grid.extent.minx, grid.extent.miny),
+                upper_right: axis_ordered(grid.extent.maxx, grid.extent.maxy),
+                crs: Some(crs),
+                ordered_axes: None,
+            }),
+            tile_matrices,
+        })
+    }
+}
+
+impl TryFrom<&Grid> for Tms {
+    type Error = TmsError;
+    fn try_from(grid: &Grid) -> Result<Self, Self::Error> {
+        Tms::try_from(&TileMatrixSet::try_from(grid)?)
+    }
+}
+
+/// Grid of a TileMatrixSet.
+///
+/// Requires zoom levels numbered from 0, a common origin and tile size and no coalesced tiles.
+impl TryFrom<&Tms> for Grid {
+    type Error = TmsError;
+    fn try_from(tms: &Tms) -> Result<Self, Self::Error> {
+        let error = |msg: &str| TmsError::GridConversionError(msg.to_string());
+        let matrices = tms.matrices();
+        if tms.minzoom() != 0 || usize::from(tms.maxzoom()) + 1 != matrices.len() {
+            return Err(error("zoom levels are not numbered from 0"));
+        }
+        let crs = tms.crs();
+        let srid = match crs.authority {
+            Authority::EPSG => crs.code.parse().ok(),
+            Authority::OGC if crs.code == "CRS84" => Some(4326),
+            Authority::OGC => None,
+        }
+        .ok_or_else(|| error(&format!("no SRID for CRS {crs}")))?;
+        let units = match crs_info(crs) {
+            None => return Err(error(&format!("unknown units for CRS {crs}"))),
+            Some(info) if info.angular_unit => Unit::Degrees,
+            Some(info) if info.unit_conv_factor == 1.0 => Unit::Meters,
+            Some(info) if (info.unit_conv_factor - 0.3048).abs() < 1e-6 => Unit::Feet,
+            Some(info) => return Err(error(&format!("unsupported unit `{}`", info.unit_name))),
+        };
+        let first = &matrices[0];
+        let origin = if bottom_left_origin(first) {
+            Origin::BottomLeft
+        } else {
+            Origin::TopLeft
+        };
+        if matrices.iter().any(|m| {
+            m.tile_width != first.tile_width
+                || m.tile_height != first.tile_height
+                || bottom_left_origin(m) != bottom_left_origin(first)
+                || m.variable_matrix_widths.is_some()
+        }) {
+            return Err(error(
+                "tile size, corner of origin or matrix width vary between levels",
+            ));
+        }
+        let bbox = tms.xy_bbox();
+        let grid = Grid::new(
+            first.tile_width.into(),
+            first.tile_height.into(),
+            Extent {
+                minx: bbox.left,
+                miny: bbox.bottom,
+                maxx: bbox.right,
+                maxy: bbox.top,
+            },
+            srid,
+            units,
+            matrices.iter().map(|m| tms.resolution(m)).collect(),
+            origin,
+        );
+        let origin_y = match grid.origin {
+            Origin::TopLeft => grid.extent.maxy,
+            Origin::BottomLeft => grid.extent.miny,
+        };
+        for (zoom, matrix) in matrices.iter().enumerate() {
+            let corner = tms.xy_corner(matrix, 0, 0);
+            let tolerance = grid.resolutions[zoom] * 1e-3;
+            if (corner.x - grid.extent.minx).abs() > tolerance
+                || (corner.y - origin_y).abs() > tolerance
+            {
+                return Err(error(&format!(
+                    "origin of level {zoom} is not a corner of the extent"
+                )));
+            }
+            let (maxx, maxy) = grid.level_max[zoom];
+            if u64::from(maxx) != u64::from(matrix.matrix_width)
+                || u64::from(maxy) != u64::from(matrix.matrix_height)
+            {
+                return Err(error(&format!(
+                    "matrix size of level {zoom} doesn't match the extent"
+                )));
+            }
+        }
+        Ok(grid)
+    }
+}
+
 /// Projected extent
 pub fn extent_wgs84_to_merc(extent: &Extent) -> Extent {
     let (minx, miny) = lonlat_to_merc(extent.minx, extent.miny);
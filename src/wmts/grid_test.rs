@@ -7,8 +7,6 @@ use crate::wmts::grid::{extent_wgs84_to_merc, lonlat_to_merc, Extent, ExtentInt,
 
 #[test]
 fn test_bbox() {
-    use std::u32;
-
     let grid = Grid::web_mercator();
 
     let extent000 = grid.tile_extent(0, 0, 0);
@@ -95,7 +93,7 @@ fn test_resolutions() {
     // Formula: http://wiki.openstreetmap.org/wiki/Slippy_map_tilenames#Resolution_and_Scale
     // const PIXEL_WIDTH_Z0: f64 = 2.0 * 6378137.0 * consts::PI / 256.0; //  = 40075016.68557849 / 256
     // Calculated pixel width without rounding results in non-symmetrical grid.tile_extent(0, 0, 0)
-    const PIXEL_WIDTH_Z0: f64 = 156543.0339280410; // rounded to 10 digits
+    const PIXEL_WIDTH_Z0: f64 = 156543.033928041; // rounded to 10 digits
     let resolutions: Vec<f64> = (0..23)
         .map(|z| PIXEL_WIDTH_Z0 / (z as f64).exp2())
         .collect();
@@ -358,3 +356,177 @@ mod web_mercator {
         );
     }
 }
+
+mod tms_conversion {
+    use crate::wmts::grid::{Extent, Grid, Origin, Unit};
+    use crate::wmts::grid_iterator::GridIterator;
+    use crate::{tms, BoundingBox, Tms, TmsError, Xyz};
+    use std::collections::BTreeSet;
+
+    fn assert_extent_eq(extent: &Extent, bounds: &BoundingBox) {
+        let tolerance = (extent.maxx - extent.minx) * 1e-9;
+        assert!(
+            (extent.minx - bounds.left).abs() < tolerance,
+            "{extent:?} {bounds:?}"
+        );
+        assert!(
+            (extent.miny - bounds.bottom).abs() < tolerance,
+            "{extent:?} {bounds:?}"
+        );
+        assert!(
+            (extent.maxx - bounds.right).abs() < tolerance,
+            "{extent:?} {bounds:?}"
+        );
+        assert!(
+            (extent.maxy - bounds.top).abs() < tolerance,
+            "{extent:?} {bounds:?}"
+        );
+    }
+
+    /// Check tile extents and iterated tiles of all grid levels
+    fn assert_same_tiles(grid: &Grid, tms: &Tms, extent: &Extent) {
+        for zoom in 0..=grid.maxzoom() {
+            let (maxx, maxy) = grid.level_limit(zoom);
+            for (x, y) in [(0, 0), (maxx - 1, maxy - 1), (maxx / 2, maxy / 3)] {
+                let bounds = tms.xy_bounds(&Xyz::new(x.into(), y.into(), zoom)).unwrap();
+                assert_extent_eq(&grid.tile_extent(x, y, zoom), &bounds);
+            }
+        }
+        let maxzoom = grid.maxzoom().min(8);
+        let grid_tiles = GridIterator::new(0, maxzoom, grid.tile_limits(extent.clone(), 0))
+            .map(|(z, x, y)| (z, u64::from(x), u64::from(y)))
+            .collect::<BTreeSet<_>>();
+        let bbox = BoundingBox::new(extent.minx, extent.miny, extent.maxx, extent.maxy);
+        let tms_tiles = tms
+            .xyz_iterator(&bbox, 0, maxzoom)
+            .unwrap()
+            .map(|tile| (tile.z, tile.x, tile.y))
+            .collect::<BTreeSet<_>>();
+        assert!(!grid_tiles.is_empty());
+        assert_eq!(grid_tiles, tms_tiles);
+    }
+
+    fn swiss_grid() -> Grid {
+        Grid::new(
+            256,
+            256,
+            Extent {
+                minx: 2420000.0,
+                miny: 1030000.0,
+                maxx: 2900000.0,
+                maxy: 1350000.0,
+            },
+            2056,
+            Unit::Meters,
+            vec![
+                4000.0, 3750.0, 3500.0, 3250.0, 3000.0, 2750.0, 2500.0, 2250.0, 2000.0, 1750.0,
+                1500.0, 1250.0, 1000.0, 750.0, 650.0, 500.0, 250.0, 100.0, 50.0, 20.0,
+            ],
+            Origin::TopLeft,
+        )
+    }
+
+    #[test]
+    fn grid_to_tms() {
+        let extent = Extent {
+            minx: 950000.0,
+            miny: 5900000.0,
+            maxx: 1200000.0,
+            maxy: 6100000.0,
+        };
+        let grid = Grid::web_mercator();
+        let tms = Tms::try_from(&grid).unwrap();
+        assert_eq!(tms.tms.id, "Grid3857");
+        assert_eq!(tms.maxzoom(), grid.maxzoom());
+        assert_same_tiles(&grid, &tms, &extent);
+
+        // Latitude first CRS
+        let grid = Grid::wgs84();
+        let tms = Tms::try_from(&grid).unwrap();
+        assert_eq!(tms.matrices()[0].point_of_origin, [-90.0, -180.0]);
+        let extent = Extent {
+            minx: 5.9,
+            miny: 45.8,
+            maxx: 10.5,
+            maxy: 47.8,
+        };
+        assert_same_tiles(&grid, &tms, &extent);
+
+        let grid = swiss_grid();
+        let tms = Tms::try_from(&grid).unwrap();
+        let extent = Extent {
+            minx: 2600000.0,
+            miny: 1200000.0,
+            maxx: 2700000.0,
+            maxy: 1250000.0,
+        };
+        assert_same_tiles(&grid, &tms, &extent);
+    }
+
+    #[test]
+    fn tms_to_grid() {
+        let mercator = tms().lookup("WebMercatorQuad").unwrap();
+        let grid = Grid::try_from(mercator.as_ref()).unwrap();
+        assert_eq!(grid.srid, 3857);
+        assert_eq!(grid.units, Unit::Meters);
+        assert_eq!(grid.origin, Origin::TopLeft);
+        assert_eq!(grid.nlevels(), 25);
+        let web_mercator = Grid::web_mercator();
+        for zoom in 0..=web_mercator.maxzoom() {
+            let (a, b) = (grid.pixel_width(zoom), web_mercator.pixel_width(zoom));
+            assert!((a - b).abs() / b < 1e-9);
+        }
+        let extent = Extent {
+            minx: 950000.0,
+            miny: 5900000.0,
+            maxx: 1200000.0,
+            maxy: 6100000.0,
+        };
+        assert_same_tiles(&grid, &mercator, &extent);
+
+        let crs84 = tms().lookup("WorldCRS84Quad").unwrap();
+        let grid = Grid::try_from(crs84.as_ref()).unwrap();
+        assert_eq!((grid.srid, grid.units.clone()), (4326, Unit::Degrees));
+        assert_eq!(grid.level_limit(0), (2, 1));
+
+        // Round trip
+        let swiss = swiss_grid();
+        let grid = Grid::try_from(&Tms::try_from(&swiss).unwrap()).unwrap();
+        assert_eq!(grid.extent, swiss.extent);
+        for zoom in 0..=swiss.maxzoom() {
+            assert_eq!(grid.pixel_width(zoom), swiss.pixel_width(zoom));
+        }
+        assert_eq!(grid.origin, swiss.origin);
+        let grid = Grid::try_from(&Tms::try_from(&Grid::wgs84()).unwrap()).unwrap();
+        assert_eq!(grid.origin, Origin::BottomLeft);
+        assert_eq!(grid.extent, Grid::wgs84().extent);
+    }
+
+    #[test]
+    fn tms_not_representable() {
+        // Coalesced tiles
+        let cdb = tms().lookup("CDB1GlobalGrid").unwrap();
+        assert!(matches!(
+            Grid::try_from(cdb.as_ref()),
+            Err(TmsError::GridConversionError(_))
+        ));
+
+        // Levels not starting at 0
+        let mut data = tms().get("WebMercatorQuad").unwrap().clone();
+        data.tile_matrices.drain(0..3);
+        let partial = Tms::try_from(&data).unwrap();
+        assert!(matches!(
+            Grid::try_from(&partial),
+            Err(TmsError::GridConversionError(_))
+        ));
+
+        // CRS with unknown units
+        let mut data = tms().get("WebMercatorQuad").unwrap().clone();
+        data.crs = ogcapi_types::common::Crs::from_epsg(32767);
+        let unknown = Tms::try_from(&data).unwrap();
+        assert!(matches!(
+            Grid::try_from(&unknown),
+            Err(TmsError::GridConversionError(_))
+        ));
+    }
+}
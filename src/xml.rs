@@ -0,0 +1,273 @@
+//! Minimal XML reader for capabilities documents.
+//!
+//! Supports elements, attributes, text, CDATA and predefined or numeric entities.
+//! Namespace prefixes are stripped from element and attribute names, end tags have to match
+//! the qualified name of the start tag.
+//!
+//! Reading WMTS capabilities only needs this small, well-formed subset of XML, so a
+//! hand-written reader avoids adding an XML crate as a dependency. DTDs are skipped and
+//! neither validation nor external entities are supported.
+
+/// XML element
+#[derive(Debug, Default)]
+pub(crate) struct Element {
+    /// Local name without namespace prefix
+    pub name: String,
+    pub attributes: Vec<(String, String)>,
+    pub children: Vec<Element>,
+    pub text: String,
+}
+
+impl Element {
+    /// First child element with the given local name.
+    pub fn child(&self, name: &str) -> Option<&Element> {
+        self.children.iter().find(|child| child.name == name)
+    }
+
+    /// All child elements with the given local name.
+    pub fn children<'a>(&'a self, name: &'a str) -> impl Iterator<Item = &'a Element> + 'a {
+        self.children.iter().filter(move |child| child.name == name)
+    }
+
+    /// Trimmed text of the first child element with the given local name.
+    pub fn child_text(&self, name: &str) -> Option<&str> {
+        self.child(name).map(|child| child.text.trim())
+    }
+
+    /// Attribute value by local name.
+    pub fn attribute(&self, name: &str) -> Option<&str> {
+        self.attributes
+            .iter()
+            .find(|(key, _)| key == name)
+            .map(|(_, value)| value.as_str())
+    }
+}
+
+/// Parse an XML document and return its root element.
+pub(crate) fn parse(xml: &str) -> Result<Element, String> {
+    // Open elements with their qualified name
+    let mut stack: Vec<(Element, &str)> = Vec::new();
+    let mut root = None;
+    let mut rest = xml;
+    while !rest.is_empty() {
+        if let Some(tail) = rest.strip_prefix("<?") {
+            rest = skip_past(tail, "?>")?;
+        } else if let Some(tail) = rest.strip_prefix("<!--") {
+            rest = skip_past(tail, "-->")?;
+        } else if let Some(tail) = rest.strip_prefix("<![CDATA[") {
+            let end = tail.find("]]>").ok_or("Unterminated CDATA section")?;
+            if let Some((current, _)) = stack.last_mut() {
+                current.text.push_str(&tail[..end]);
+            }
+            rest = &tail[end + 3..];
+        } else if let Some(tail) = rest.strip_prefix("<!") {
+            rest = skip_declaration(tail)?;
+        } else if let Some(tail) = rest.strip_prefix("</") {
+            let end = tail.find('>').ok_or("Unterminated end tag")?;
+            let name = tail[..end].trim_end();
+            let (element, qualified_name) = stack.pop().ok_or("Unexpected end tag")?;
+            if name != qualified_name {
+                return Err(format!(
+                    "Expected end tag `{qualified_name}`, found `{name}`"
+                ));
+            }
+            match stack.last_mut() {
+                Some((parent, _)) => parent.children.push(element),
+                None => root = Some(element),
+            }
+            rest = &tail[end + 1..];
+        } else if let Some(tail) = rest.strip_prefix('<') {
+            let (element, qualified_name, empty, tail) = parse_start_tag(tail)?;
+            if stack.is_empty() && root.is_some() {
+                return Err(format!("Unexpected second root element `{qualified_name}`"));
+            }
+            if empty {
+                match stack.last_mut() {
+                    Some((parent, _)) => parent.children.push(element),
+                    None => root = Some(element),
+                }
+            } else {
+                stack.push((element, qualified_name));
+            }
+            rest = tail;
+        } else {
+            let end = rest.find('<').unwrap_or(rest.len());
+            let text = decode_entities(&rest[..end])?;
+            match stack.last_mut() {
+                Some((current, _)) => current.text.push_str(&text),
+                None if text.trim().is_empty() => {}
+                None => return Err("Text outside of root element".to_string()),
+            }
+            rest = &rest[end..];
+        }
+    }
+    if let Some((_, qualified_name)) = stack.last() {
+        return Err(format!("Unclosed element `{qualified_name}`"));
+    }
+    root.ok_or_else(|| "No root element".to_string())
+}
+
+fn skip_past<'a>(xml: &'a str, delimiter: &str) -> Result<&'a str, String> {
+    xml.find(delimiter)
+        .map(|end| &xml[end + delimiter.len()..])
+        .ok_or_else(|| format!("Missing `{delimiter}`"))
+}
+
+/// Skip a `<!DOCTYPE ...>` declaration including an internal subset.
+fn skip_declaration(xml: &str) -> Result<&str, String> {
+    let mut depth = 0;
+    for (idx, c) in xml.char_indices() {
+        match c {
+            '[' => depth += 1,
+            ']' => depth -= 1,
+            '>' if depth == 0 => return Ok(&xml[idx + 1..]),
+            _ => {}
+        }
+    }
+    Err("Unterminated declaration".to_string())
+}
+
+/// Parse a start tag after the opening `<`.
+///
+/// Returns the element, its qualified name, whether it is an empty element tag and the
+/// remaining input.
+fn parse_start_tag(xml: &str) -> Result<(Element, &str, bool, &str), String> {
+    let name_end = find_name_end(xml).ok_or("Unterminated start tag")?;
+    let qualified_name = &xml[..name_end];
+    if qualified_name.is_empty() {
+        return Err("Missing element name".to_string());
+    }
+    let mut element = Element {
+        name: local_name(qualified_name).to_string(),
+        ..Default::default()
+    };
+    let mut rest = &xml[name_end..];
+    loop {
+        let tail = rest.trim_start();
+        if let Some(tail) = tail.strip_prefix("/>") {
+            return Ok((element, qualified_name, true, tail));
+        }
+        if let Some(tail) = tail.strip_prefix('>') {
+            return Ok((element, qualified_name, false, tail));
+        }
+        let invalid = || format!("Invalid attribute in `{qualified_name}`");
+        // Attributes are separated by whitespace
+        if tail.len() == rest.len() {
+            return Err(invalid());
+        }
+        let key_end = find_name_end(tail).ok_or("Unterminated start tag")?;
+        let key = local_name(&tail[..key_end]).to_string();
+        let value = tail[key_end..]
+            .trim_start()
+            .strip_prefix('=')
+            .filter(|_| key_end > 0)
+            .ok_or_else(invalid)?
+            .trim_start();
+        let quote = value
+            .chars()
+            .next()
+            .filter(|c| *c == '"' || *c == '\'')
+            .ok_or_else(|| format!("Unquoted attribute `{key}`"))?;
+        // Attribute values must not contain `<`, which also keeps the scan within the tag
+        let end = value[1..]
+            .find([quote, '<'])
+            .filter(|end| !value[end + 1..].starts_with('<'))
+            .ok_or_else(|| format!("Unterminated attribute `{key}`"))?;
+        element
+            .attributes
+            .push((key, decode_entities(&value[1..end + 1])?));
+        rest = &value[end + 2..];
+    }
+}
+
+/// End of an element or attribute name.
+fn find_name_end(xml: &str) -> Option<usize> {
+    xml.find(|c: char| c.is_whitespace() || matches!(c, '>' | '/' | '=' | '<'))
+}
+
+fn local_name(name: &str) -> &str {
+    name.rsplit(':').next().unwrap_or(name)
+}
+
+fn decode_entities(text: &str) -> Result<String, String> {
+    let mut decoded = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(start) = rest.find('&') {
+        decoded.push_str(&rest[..start]);
+        let end = rest[start..]
+            .find(';')
+            .ok_or("Unterminated entity reference")?;
+        let entity = &rest[start + 1..start + end];
+        let c = match entity {
+            "amp" => Some('&'),
+            "lt" => Some('<'),
+            "gt" => Some('>'),
+            "quot" => Some('"'),
+            "apos" => Some('\''),
+            _ => {
+                if let Some(hex) = entity.strip_prefix("#x") {
+                    u32::from_str_radix(hex, 16).ok().and_then(char::from_u32)
+                } else if let Some(dec) = entity.strip_prefix('#') {
+                    dec.parse().ok().and_then(char::from_u32)
+                } else {
+                    None
+                }
+            }
+        };
+        decoded.push(c.ok_or_else(|| format!("Unknown entity `&{entity};`"))?);
+        rest = &rest[start + end + 1..];
+    }
+    decoded.push_str(rest);
+    Ok(decoded)
+}
+
+#[cfg(test)]
+mod test {
+    use super::parse;
+
+    #[test]
+    fn parse_document() {
+        let root = parse(
+            r#"<?xml version="1.0"?>
+<!DOCTYPE doc [<!ENTITY x "y">]>
+<!-- comment -->
+<ows:Doc xmlns:ows="http://www.opengis.net/ows/1.1" version='1.0'>
+  <ows:Title>A &amp; B &#x41;&#66;</ows:Title>
+  <Empty attr="&lt;1&gt;"/>
+  <Data><![CDATA[<raw>]]></Data>
+  <Data>2</Data>
+</ows:Doc>"#,
+        )
+        .unwrap();
+        assert_eq!(root.name, "Doc");
+        assert_eq!(root.attribute("version"), Some("1.0"));
+        assert_eq!(root.child_text("Title"), Some("A & B AB"));
+        assert_eq!(root.child("Empty").unwrap().attribute("attr"), Some("<1>"));
+        assert_eq!(root.children("Data").count(), 2);
+        assert_eq!(root.child_text("Data"), Some("<raw>"));
+    }
+
+    #[test]
+    fn parse_errors() {
+        assert!(parse("<a><b></a>").is_err());
+        assert!(parse("<a>").is_err());
+        assert!(parse("<a:x b = '1' ></a:x >").is_ok());
+        assert!(parse("<a:x></b:x>").is_err());
+        assert!(parse("<a:x></x>").is_err());
+        assert!(parse("<a:x></ a:x>").is_err());
+        assert!(parse("<a x><b y='1'/></a>").is_err());
+        assert!(parse("<a x='1'y='2'/>").is_err());
+        assert!(parse("<a ='1'/>").is_err());
+        assert!(parse("<a x=1/>").is_err());
+        assert!(parse("<a x='1/>").is_err());
+        assert!(parse("<a x='1/><b y='2'/>").is_err());
+        assert!(parse("<a x=\"1/><b/>\" />").is_err());
+        assert!(parse("< a/>").is_err());
+        assert!(parse("<a<b/>").is_err());
+        assert!(parse("<a>&unknown;</a>").is_err());
+        assert!(parse("text").is_err());
+        assert!(parse("<a/><b/>").is_err());
+        assert!(parse("<a></a><b></b>").is_err());
+        assert!(parse("").is_err());
+    }
+}
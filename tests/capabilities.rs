@@ -0,0 +1,301 @@
+use ogcapi_types::common::Crs;
+use ogcapi_types::tiles::CornerOfOrigin;
+use std::sync::Arc;
+use tile_grid::*;
+
+fn layer(tile_matrix_sets: Vec<Arc<Tms>>) -> WmtsLayer {
+    WmtsLayer {
+        identifier: "swiss".to_string(),
+        title: "Switzerland & Liechtenstein".to_string(),
+        formats: vec!["image/png".to_string()],
+        extent: BoundingBox::new(5.9, 45.8, 10.5, 47.8),
+        minzoom: 0,
+        maxzoom: 8,
+        tile_matrix_sets,
+        resource_url: Some("https://example.com/{TileMatrix}/{TileCol}/{TileRow}.png".to_string()),
+    }
+}
+
+#[test]
+fn test_wmts_tile_matrix_set() {
+    let xml = tms()
+        .lookup("WebMercatorQuad")
+        .unwrap()
+        .wmts_tile_matrix_set()
+        .unwrap();
+    assert!(xml.starts_with("<TileMatrixSet>\n<ows:Title>Google Maps Compatible for the World</ows:Title>\n<ows:Identifier>WebMercatorQuad</ows:Identifier>\n"));
+    assert!(xml.contains("<ows:SupportedCRS>urn:ogc:def:crs:EPSG::3857</ows:SupportedCRS>"));
+    assert!(xml.contains(
+        "<WellKnownScaleSet>urn:ogc:def:wkss:OGC:1.0:GoogleMapsCompatible</WellKnownScaleSet>"
+    ));
+    assert!(xml.contains(
+        "<TileMatrix>
+<ows:Identifier>2</ows:Identifier>
+<ScaleDenominator>139770566.007179</ScaleDenominator>
+<TopLeftCorner>-20037508.3427892 20037508.3427892</TopLeftCorner>
+<TileWidth>256</TileWidth>
+<TileHeight>256</TileHeight>
+<MatrixWidth>4</MatrixWidth>
+<MatrixHeight>4</MatrixHeight>
+</TileMatrix>"
+    ));
+    assert_eq!(xml.matches("<TileMatrix>").count(), 25);
+
+    // Latitude first
+    let xml = tms()
+        .lookup("WorldCRS84Quad")
+        .unwrap()
+        .wmts_tile_matrix_set()
+        .unwrap();
+    assert!(xml.contains("<ows:SupportedCRS>urn:ogc:def:crs:EPSG::4326</ows:SupportedCRS>"));
+    assert!(xml.contains("<TopLeftCorner>90 -180</TopLeftCorner>"));
+
+    // Bottom left origin is described by its top left corner
+    let mut data = tms().get("WebMercatorQuad").unwrap().clone();
+    for matrix in data.tile_matrices.iter_mut() {
+        matrix.corner_of_origin = Some(CornerOfOrigin::BottomLeft);
+        matrix.point_of_origin[1] = -matrix.point_of_origin[1];
+    }
+    let flipped = Tms::try_from(&data).unwrap();
+    let xml = flipped.wmts_tile_matrix_set().unwrap();
+    assert!(xml.contains("<TopLeftCorner>-20037508.3427892 20037508.3427892</TopLeftCorner>"));
+}
+
+#[test]
+fn test_wmts_capabilities() {
+    let mercator = tms().lookup("WebMercatorQuad").unwrap();
+    let crs84 = tms().lookup("WorldCRS84Quad").unwrap();
+    let layers = [
+        layer(vec![mercator.clone(), crs84]),
+        WmtsLayer {
+            identifier: "other".to_string(),
+            ..layer(vec![mercator.clone()])
+        },
+    ];
+    let xml = wmts_capabilities(&layers).unwrap();
+    assert!(xml.starts_with("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n<Capabilities xmlns=\"http://www.opengis.net/wmts/1.0\""));
+    assert!(xml.ends_with("</Contents>\n</Capabilities>\n"));
+    assert_eq!(xml.matches("<Layer>").count(), 2);
+    assert_eq!(xml.matches("<TileMatrixSet>\n").count(), 2);
+    assert!(xml.contains("<ows:Title>Switzerland &amp; Liechtenstein</ows:Title>"));
+    assert!(xml.contains("<ows:WGS84BoundingBox>\n<ows:LowerCorner>5.9 45.8</ows:LowerCorner>\n<ows:UpperCorner>10.5 47.8</ows:UpperCorner>\n</ows:WGS84BoundingBox>"));
+    assert!(xml.contains(
+        "<TileMatrixLimits>
+<TileMatrix>8</TileMatrix>
+<MinTileRow>89</MinTileRow>
+<MaxTileRow>91</MaxTileRow>
+<MinTileCol>132</MinTileCol>
+<MaxTileCol>135</MaxTileCol>
+</TileMatrixLimits>"
+    ));
+    assert!(xml.contains("<ResourceURL format=\"image/png\" resourceType=\"tile\" template=\"https://example.com/{TileMatrix}/{TileCol}/{TileRow}.png\"/>"));
+
+    // Rows of a bottom left origin are counted from the top
+    let mut data = tms().get("WebMercatorQuad").unwrap().clone();
+    for matrix in data.tile_matrices.iter_mut() {
+        matrix.corner_of_origin = Some(CornerOfOrigin::BottomLeft);
+        matrix.point_of_origin[1] = -matrix.point_of_origin[1];
+    }
+    let flipped = Arc::new(Tms::try_from(&data).unwrap());
+    let flipped_xml = wmts_capabilities(&[layer(vec![flipped])]).unwrap();
+    let limits = |xml: &str| {
+        xml[xml.find("<TileMatrixSetLimits>").unwrap()..xml.find("</TileMatrixSetLimits>").unwrap()]
+            .to_string()
+    };
+    assert_eq!(limits(&flipped_xml), limits(&xml));
+
+    // No limits without tile matrices in the zoom range
+    let xml = wmts_capabilities(&[WmtsLayer {
+        minzoom: 30,
+        maxzoom: 32,
+        ..layer(vec![mercator.clone()])
+    }])
+    .unwrap();
+    assert!(xml.contains(
+        "<TileMatrixSetLink>\n<TileMatrixSet>WebMercatorQuad</TileMatrixSet>\n</TileMatrixSetLink>"
+    ));
+    assert!(!xml.contains("<TileMatrixSetLimits>"));
+
+    // Coalesced tiles are not supported
+    for name in ["GNOSISGlobalGrid", "CDB1GlobalGrid"] {
+        let grid = tms().lookup(name).unwrap();
+        assert!(matches!(
+            grid.wmts_tile_matrix_set(),
+            Err(TmsError::CapabilitiesError(_))
+        ));
+        assert!(matches!(
+            wmts_capabilities(&[layer(vec![grid])]),
+            Err(TmsError::CapabilitiesError(_))
+        ));
+    }
+}
+
+#[test]
+fn test_parse_wmts_roundtrip() {
+    let mercator = tms().lookup("WebMercatorQuad").unwrap();
+    let crs84 = tms().lookup("WorldCRS84Quad").unwrap();
+    let xml = wmts_capabilities(&[layer(vec![mercator.clone(), crs84.clone()])]).unwrap();
+    let sets = parse_wmts_tile_matrix_sets(&xml).unwrap();
+    assert_eq!(sets.len(), 2);
+    for (parsed, original) in sets.iter().zip([&mercator, &crs84]) {
+        assert_eq!(parsed.id, original.tms.id);
+        assert_eq!(parsed.crs, original.tms.crs);
+        assert_eq!(
+            parsed.well_known_scale_set,
+            original.tms.well_known_scale_set
+        );
+        assert_eq!(
+            parsed.title_description_keywords.title,
+            original.tms.title_description_keywords.title
+        );
+        assert_eq!(parsed.tile_matrices.len(), original.matrices().len());
+        for (a, b) in parsed.tile_matrices.iter().zip(original.matrices()) {
+            assert_eq!(a.id, b.id);
+            assert_eq!(a.scale_denominator, b.scale_denominator);
+            assert!((a.cell_size - b.cell_size).abs() / b.cell_size < 1e-9);
+            assert_eq!(a.point_of_origin, b.point_of_origin);
+            assert_eq!(a.matrix_width, b.matrix_width);
+            assert_eq!(a.matrix_height, b.matrix_height);
+        }
+    }
+
+    let mut registry = TileMatrixSets::new();
+    registry.register(sets, false).unwrap();
+    let parsed = registry.lookup("WorldCRS84Quad").unwrap();
+    assert_eq!(
+        parsed.xy_bounds(&Xyz::new(1, 0, 1)).unwrap(),
+        crs84.xy_bounds(&Xyz::new(1, 0, 1)).unwrap()
+    );
+}
+
+const THIRD_PARTY_CAPABILITIES: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<Capabilities xmlns="http://www.opengis.net/wmts/1.0" xmlns:ows="http://www.opengis.net/ows/1.1" version="1.0.0">
+  <ows:ServiceIdentification>
+    <ows:Title>Third party WMTS</ows:Title>
+  </ows:ServiceIdentification>
+  <Contents>
+    <Layer>
+      <ows:Identifier>basemap</ows:Identifier>
+      <TileMatrixSetLink>
+        <TileMatrixSet>2056_27</TileMatrixSet>
+      </TileMatrixSetLink>
+    </Layer>
+    <TileMatrixSet>
+      <ows:Identifier>2056_27</ows:Identifier>
+      <ows:Abstract>Swiss LV95 &amp; friends</ows:Abstract>
+      <ows:SupportedCRS>urn:ogc:def:crs:EPSG:2056</ows:SupportedCRS>
+      <TileMatrix>
+        <ows:Identifier>L01</ows:Identifier>
+        <ScaleDenominator>7142857.142857143</ScaleDenominator>
+        <TopLeftCorner>2420000.0 1350000.0</TopLeftCorner>
+        <TileWidth>256</TileWidth>
+        <TileHeight>256</TileHeight>
+        <MatrixWidth>1</MatrixWidth>
+        <MatrixHeight>1</MatrixHeight>
+      </TileMatrix>
+      <TileMatrix>
+        <ows:Identifier>L00</ows:Identifier>
+        <ScaleDenominator>14285714.285714287</ScaleDenominator>
+        <TopLeftCorner>2420000.0 1350000.0</TopLeftCorner>
+        <TileWidth>256</TileWidth>
+        <TileHeight>256</TileHeight>
+        <MatrixWidth>1</MatrixWidth>
+        <MatrixHeight>1</MatrixHeight>
+      </TileMatrix>
+    </TileMatrixSet>
+    <TileMatrixSet>
+      <ows:Identifier>EPSG:4326</ows:Identifier>
+      <ows:SupportedCRS>EPSG:4326</ows:SupportedCRS>
+      <WellKnownScaleSet>urn:ogc:def:wkss:OGC:1.0:GoogleCRS84Quad</WellKnownScaleSet>
+      <TileMatrix>
+        <ows:Identifier>EPSG:4326:0</ows:Identifier>
+        <ScaleDenominator>279541132.01435887</ScaleDenominator>
+        <TopLeftCorner>-180.0 90.0</TopLeftCorner>
+        <TileWidth>256</TileWidth>
+        <TileHeight>256</TileHeight>
+        <MatrixWidth>2</MatrixWidth>
+        <MatrixHeight>1</MatrixHeight>
+      </TileMatrix>
+    </TileMatrixSet>
+    <TileMatrixSet>
+      <ows:Identifier>CRS84</ows:Identifier>
+      <ows:SupportedCRS>urn:ogc:def:crs:OGC:1.3:CRS84</ows:SupportedCRS>
+      <TileMatrix>
+        <ows:Identifier>0</ows:Identifier>
+        <ScaleDenominator>279541132.01435887</ScaleDenominator>
+        <TopLeftCorner>-180.0 90.0</TopLeftCorner>
+        <TileWidth>256</TileWidth>
+        <TileHeight>256</TileHeight>
+        <MatrixWidth>2</MatrixWidth>
+        <MatrixHeight>1</MatrixHeight>
+      </TileMatrix>
+    </TileMatrixSet>
+  </Contents>
+</Capabilities>
+"#;
+
+#[test]
+fn test_parse_wmts_third_party() {
+    let sets = parse_wmts_tile_matrix_sets(THIRD_PARTY_CAPABILITIES).unwrap();
+    assert_eq!(sets.len(), 3);
+
+    let swiss = &sets[0];
+    assert_eq!(swiss.id, "2056_27");
+    assert_eq!(swiss.crs, Crs::from_epsg(2056));
+    assert_eq!(
+        swiss.title_description_keywords.description.as_deref(),
+        Some("Swiss LV95 & friends")
+    );
+    assert_eq!(swiss.tile_matrices[0].id, "L01");
+    assert!((swiss.tile_matrices[1].cell_size - 4000.0).abs() < 1e-6);
+    let grid = Tms::try_from(swiss).unwrap();
+    assert_eq!(grid.matrix_id(0), Some("L00"));
+    assert_eq!(grid.matrix_id(1), Some("L01"));
+    assert_eq!(
+        grid.xy_bounds(&Xyz::new(0, 0, 0)).unwrap(),
+        BoundingBox::new(2420000.0, 326000.0, 3444000.0, 1350000.0)
+    );
+
+    // Longitude first TopLeftCorner in a latitude first CRS
+    let wgs84 = &sets[1];
+    assert_eq!(wgs84.crs, Crs::from_epsg(4326));
+    assert_eq!(wgs84.tile_matrices[0].point_of_origin, [90.0, -180.0]);
+    assert_eq!(
+        wgs84.well_known_scale_set.as_deref(),
+        Some("http://www.opengis.net/def/wkss/OGC/1.0/GoogleCRS84Quad")
+    );
+    let grid = Tms::try_from(wgs84).unwrap();
+    assert_eq!(
+        grid.xy_bounds(&Xyz::new(1, 0, 0)).unwrap(),
+        BoundingBox::new(0.0, -90.0, 180.0, 90.0)
+    );
+
+    let crs84 = &sets[2];
+    assert_eq!(crs84.crs, Crs::default());
+    assert_eq!(crs84.tile_matrices[0].point_of_origin, [-180.0, 90.0]);
+}
+
+#[test]
+fn test_parse_wmts_errors() {
+    assert!(matches!(
+        parse_wmts_tile_matrix_sets("<Capabilities><Contents>"),
+        Err(TileMatrixSetError::XmlError(_))
+    ));
+    assert!(matches!(
+        parse_wmts_tile_matrix_sets("<Capabilities/>"),
+        Err(TileMatrixSetError::CapabilitiesError(_))
+    ));
+    let xml = THIRD_PARTY_CAPABILITIES.replace(
+        "EPSG:4326</ows:SupportedCRS>",
+        "ESRI:102100</ows:SupportedCRS>",
+    );
+    assert!(matches!(
+        parse_wmts_tile_matrix_sets(&xml),
+        Err(TileMatrixSetError::CapabilitiesError(msg)) if msg.contains("ESRI:102100")
+    ));
+    let xml = THIRD_PARTY_CAPABILITIES.replace(
+        "<MatrixHeight>1</MatrixHeight>",
+        "<MatrixHeight>0</MatrixHeight>",
+    );
+    assert!(parse_wmts_tile_matrix_sets(&xml).is_err());
+}
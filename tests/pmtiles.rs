@@ -0,0 +1,88 @@
+use std::ops::RangeInclusive;
+use tile_grid::*;
+
+#[test]
+fn test_pmtiles_tile_id() {
+    let tms = tms().lookup("WebMercatorQuad").unwrap();
+    // Reference vectors from the PMTiles v3 specification
+    let vectors = [
+        (Xyz::new(0, 0, 0), 0),
+        (Xyz::new(0, 0, 1), 1),
+        (Xyz::new(0, 1, 1), 2),
+        (Xyz::new(1, 1, 1), 3),
+        (Xyz::new(1, 0, 1), 4),
+        (Xyz::new(0, 0, 2), 5),
+    ];
+    for (tile, tile_id) in vectors {
+        assert_eq!(tms.pmtiles_tile_id(&tile).unwrap(), tile_id, "{tile}");
+        assert_eq!(tms.pmtiles_tile(tile_id).unwrap(), tile);
+    }
+
+    for z in 0..=31 {
+        let max = (1u64 << z) - 1;
+        for tile in [
+            Xyz::new(0, 0, z),
+            Xyz::new(max, 0, z),
+            Xyz::new(0, max, z),
+            Xyz::new(max, max, z),
+        ] {
+            let tile_id = tms.pmtiles_tile_id(&tile).unwrap();
+            assert_eq!(tms.pmtiles_tile(tile_id).unwrap(), tile);
+        }
+    }
+    let last = tms
+        .pmtiles_tile_id(&Xyz::new((1 << 31) - 1, 0, 31))
+        .unwrap();
+    assert_eq!(last, 6148914691236517204);
+    assert!(matches!(
+        tms.pmtiles_tile(last + 1),
+        Err(TmsError::PmTilesError(_))
+    ));
+    assert!(tms.pmtiles_tile_id(&Xyz::new(0, 0, 32)).is_err());
+    assert!(tms.pmtiles_tile_id(&Xyz::new(2, 0, 1)).is_err());
+
+    let crs84 = tile_grid::tms().lookup("WorldCRS84Quad").unwrap();
+    assert!(matches!(
+        crs84.pmtiles_tile_id(&Xyz::new(0, 0, 0)),
+        Err(TmsError::NoQuadkeySupport)
+    ));
+}
+
+#[test]
+fn test_pmtiles_tile_ids() {
+    let tms = tms().lookup("WebMercatorQuad").unwrap();
+    let ids = |range: &TileRange| tms.pmtiles_tile_ids(range).unwrap();
+
+    assert_eq!(ids(&tms.matrix_range(0).unwrap()), vec![0..=0]);
+    assert_eq!(ids(&tms.matrix_range(3).unwrap()), vec![21..=84]);
+    assert_eq!(
+        ids(&TileRange::new(3, 0, 3, 0, 3, &tms).unwrap()),
+        vec![21..=36]
+    );
+
+    // Runs cover exactly the tiles of the range, in ascending order
+    for range in [
+        TileRange::new(5, 3, 17, 9, 30, &tms).unwrap(),
+        TileRange::new(10, 486, 500, 332, 340, &tms).unwrap(),
+    ] {
+        let runs = ids(&range);
+        assert!(runs
+            .windows(2)
+            .all(|pair| pair[0].end() + 1 < *pair[1].start()));
+        let mut expected = range
+            .tiles()
+            .map(|tile| tms.pmtiles_tile_id(&tile).unwrap())
+            .collect::<Vec<_>>();
+        expected.sort_unstable();
+        let ids = runs
+            .into_iter()
+            .flat_map(RangeInclusive::into_iter)
+            .collect::<Vec<_>>();
+        assert_eq!(ids, expected);
+    }
+
+    let crs84 = tile_grid::tms().lookup("WorldCRS84Quad").unwrap();
+    assert!(tms
+        .pmtiles_tile_ids(&crs84.matrix_range(2).unwrap())
+        .is_err());
+}
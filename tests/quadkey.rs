@@ -4,19 +4,16 @@ use tile_grid::*;
 fn test_quadkey_support() {
     let tests = vec![
         // ("LINZAntarticaMapTilegrid", false),
-        //"CDB1GlobalGrid", // Error("missing field `coalesc`", line: 19, column: 67)
+        ("CDB1GlobalGrid", false),
         #[cfg(feature = "projtransform")]
         ("EuropeanETRS89_LAEAQuad", true),
-        //"GNOSISGlobalGrid", // Error("missing field `coalesc`", line: 31, column: 66)
+        ("GNOSISGlobalGrid", false),
         #[cfg(feature = "projtransform")]
         ("CanadianNAD83_LCC", false),
-        #[cfg(feature = "projtransform")]
         ("UPSArcticWGS84Quad", true),
         //("NZTM2000", false),
         //("NZTM2000Quad", true),
-        #[cfg(feature = "projtransform")]
         ("UTM31WGS84Quad", false),
-        #[cfg(feature = "projtransform")]
         ("UPSAntarcticWGS84Quad", true),
         ("WorldMercatorWGS84Quad", true),
         //?("WGS1984Quad", false),
@@ -26,7 +23,7 @@ fn test_quadkey_support() {
     let registry = tms();
     for (name, result) in tests.into_iter() {
         dbg!(&name);
-        let tms: Tms = registry.lookup(name).unwrap();
+        let tms = registry.lookup(name).unwrap();
         assert_eq!(tms.is_quadtree, result);
     }
 }
@@ -35,7 +32,7 @@ fn test_quadkey_support() {
 fn test_quadkey() {
     let tms = tms().lookup("WebMercatorQuad").unwrap();
     let expected = "0313102310".to_string();
-    assert_eq!(tms.quadkey(&Xyz::new(486, 332, 10)), expected);
+    assert_eq!(tms.quadkey(&Xyz::new(486, 332, 10)).unwrap(), expected);
 }
 
 #[test]
@@ -43,7 +40,7 @@ fn test_quadkey_to_tile() {
     let tms = tms().lookup("WebMercatorQuad").unwrap();
     let qk = "0313102310".to_string();
     let expected = Xyz::new(486, 332, 10);
-    assert_eq!(tms.quadkey_to_tile(&qk), expected);
+    assert_eq!(tms.quadkey_to_tile(&qk).unwrap(), expected);
 }
 
 #[test]
@@ -52,13 +49,138 @@ fn test_empty_quadkey_to_tile() {
     let tms = tms().lookup("WebMercatorQuad").unwrap();
     let qk = "";
     let expected = Xyz::new(0, 0, 0);
-    assert_eq!(tms.quadkey_to_tile(qk), expected);
+    assert_eq!(tms.quadkey_to_tile(qk).unwrap(), expected);
+}
+
+#[test]
+fn test_quadkey_failure() {
+    let registry = tms();
+    let tms = registry.lookup("WebMercatorQuad").unwrap();
+    assert!(matches!(
+        tms.quadkey_to_tile("lolwut"),
+        Err(TmsError::QuadKeyError(_))
+    ));
+    assert!(tms.quadkey_to_tile(&"0".repeat(65)).is_err());
+    assert!(tms.quadkey(&Xyz::new(0, 0, 65)).is_err());
+
+    let tms = registry.lookup("WorldCRS84Quad").unwrap();
+    assert!(matches!(
+        tms.quadkey(&Xyz::new(0, 0, 1)),
+        Err(TmsError::NoQuadkeySupport)
+    ));
+    assert!(matches!(
+        tms.quadkey_to_tile("0"),
+        Err(TmsError::NoQuadkeySupport)
+    ));
+}
+
+#[test]
+fn test_quadbin() {
+    let mercator = tms().lookup("WebMercatorQuad").unwrap();
+    // Reference values from the CARTO Quadbin implementation
+    let tests = [
+        (Xyz::new(0, 0, 0), 5192650370358181887),
+        (Xyz::new(9, 8, 4), 5209574053332910079),
+    ];
+    for (tile, cell) in tests {
+        assert_eq!(mercator.quadbin(&tile).unwrap(), Quadbin(cell));
+        assert_eq!(mercator.quadbin_to_tile(Quadbin(cell)).unwrap(), tile);
+    }
+
+    let tile = Xyz::new(486, 332, 10);
+    let cell = Quadbin::from_tile(&tile).unwrap();
+    assert!(cell.is_valid());
+    assert_eq!(cell.zoom(), 10);
+    assert_eq!(cell.to_tile().unwrap(), tile);
+    assert_eq!(
+        cell.parent().unwrap().to_tile().unwrap(),
+        Xyz::new(243, 166, 9)
+    );
+    let children = cell
+        .children()
+        .unwrap()
+        .map(|child| child.to_tile().unwrap());
+    assert_eq!(
+        children,
+        [
+            Xyz::new(972, 664, 11),
+            Xyz::new(973, 664, 11),
+            Xyz::new(972, 665, 11),
+            Xyz::new(973, 665, 11),
+        ]
+    );
+    for child in cell.children().unwrap() {
+        assert_eq!(child.parent(), Some(cell));
+    }
+    assert_eq!(Quadbin(5192650370358181887).parent(), None);
+
+    let max = (1 << 26) - 1;
+    let cell = Quadbin::from_tile(&Xyz::new(max, 0, 26)).unwrap();
+    assert_eq!(cell.to_tile().unwrap(), Xyz::new(max, 0, 26));
+    assert_eq!(cell.children(), None);
+
+    assert!(!Quadbin(0).is_valid());
+    assert!(!Quadbin(5209574053332910079 - 1).is_valid());
+    // Reserved bits 57-58 set
+    assert!(!Quadbin(5209574053332910079 | 1 << 57).is_valid());
+    assert!(matches!(
+        Quadbin(0).to_tile(),
+        Err(TmsError::QuadKeyError(_))
+    ));
+    assert!(Quadbin::from_tile(&Xyz::new(0, 0, 27)).is_err());
+    assert!(Quadbin::from_tile(&Xyz::new(2, 0, 1)).is_err());
+
+    let crs84 = tms().lookup("WorldCRS84Quad").unwrap();
+    assert!(matches!(
+        crs84.quadbin(&Xyz::new(0, 0, 0)),
+        Err(TmsError::NoQuadkeySupport)
+    ));
 }
 
-// #[test]
-// fn test_quadkey_failure() -> Result<(), Error> {
-//     let registry = tms();
-//     let tms = registry.get("WebMercatorQuad").unwrap();
-//     assert!(tms.quadkey_to_tile("lolwut").is_err());
-//     Ok(())
-// }
+#[test]
+fn test_tile_key() {
+    let tile = Xyz::new(486, 332, 10);
+    let key = TileKey::from_tile(&tile).unwrap();
+    assert_eq!(key, TileKey((10 << 58) | (486 << 29) | 332));
+    assert_eq!(key.zoom(), 10);
+    assert_eq!(key.to_tile().unwrap(), tile);
+    assert_eq!(
+        key.parent().unwrap().to_tile().unwrap(),
+        Xyz::new(243, 166, 9)
+    );
+    let children = key
+        .children()
+        .unwrap()
+        .map(|child| child.to_tile().unwrap());
+    assert_eq!(
+        children,
+        [
+            Xyz::new(972, 664, 11),
+            Xyz::new(973, 664, 11),
+            Xyz::new(972, 665, 11),
+            Xyz::new(973, 665, 11),
+        ]
+    );
+    assert_eq!(TileKey(0).parent(), None);
+
+    // Keys sort like tiles
+    let mut tiles = vec![Xyz::new(3, 0, 2), Xyz::new(0, 1, 1), Xyz::new(1, 0, 2)];
+    let mut keys = tiles
+        .iter()
+        .map(|tile| TileKey::from_tile(tile).unwrap())
+        .collect::<Vec<_>>();
+    tiles.sort();
+    keys.sort();
+    let sorted = keys
+        .iter()
+        .map(|key| key.to_tile().unwrap())
+        .collect::<Vec<_>>();
+    assert_eq!(sorted, tiles);
+
+    let max = (1 << 29) - 1;
+    let key = TileKey::from_tile(&Xyz::new(max, max, 29)).unwrap();
+    assert_eq!(key.to_tile().unwrap(), Xyz::new(max, max, 29));
+    assert_eq!(key.children(), None);
+    assert!(TileKey::from_tile(&Xyz::new(0, 0, 30)).is_err());
+    assert!(TileKey((1 << 58) | 2).to_tile().is_err());
+}
@@ -0,0 +1,23 @@
+use tile_grid::*;
+
+#[test]
+fn test_tilematrixset_directory() {
+    let dir = std::env::temp_dir().join("tile-grid-test-tilematrixset-directory");
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(&dir).unwrap();
+    let custom = std::fs::read_to_string("./data/WorldCRS84Quad.json")
+        .unwrap()
+        .replace(r#""id": "WorldCRS84Quad""#, r#""id": "MyNationalGrid""#);
+    std::fs::write(dir.join("MyNationalGrid.json"), custom).unwrap();
+    std::fs::write(dir.join("Invalid.json"), "{}").unwrap();
+
+    // Must be set before the first access of the global registry
+    std::env::set_var("TILEMATRIXSET_DIRECTORY", &dir);
+    assert!(tms().lookup("MyNationalGrid").is_ok());
+    assert!(tms().lookup("WebMercatorQuad").is_ok());
+    let errors = tms_load_errors();
+    assert_eq!(errors.len(), 1);
+    assert_eq!(errors[0].0, dir.join("Invalid.json"));
+    assert!(matches!(errors[0].1, Error::TileMatrixSetError(_)));
+    std::fs::remove_dir_all(&dir).unwrap();
+}
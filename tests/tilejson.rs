@@ -0,0 +1,155 @@
+use ogcapi_types::tiles::CornerOfOrigin;
+use tile_grid::*;
+
+fn assert_approx(values: &[f64], expected: &[f64]) {
+    assert_eq!(values.len(), expected.len());
+    for (value, expected) in values.iter().zip(expected) {
+        assert!(
+            (value - expected).abs() < 1e-9,
+            "{values:?} != {expected:?}"
+        );
+    }
+}
+
+#[test]
+fn test_tilejson_defaults() {
+    let mercator = tms().lookup("WebMercatorQuad").unwrap();
+    let tilejson = TileJsonBuilder::new(&mercator)
+        .tiles(["https://example.com/tiles/{z}/{x}/{y}.pbf"])
+        .build()
+        .unwrap();
+    assert_eq!(tilejson.tilejson, "3.0.0");
+    assert_eq!((tilejson.minzoom, tilejson.maxzoom), (Some(0), Some(24)));
+    assert_eq!(tilejson.scheme.as_deref(), Some("xyz"));
+    assert_approx(
+        &tilejson.bounds.unwrap(),
+        &[-180.0, -85.0511287798066, 180.0, 85.0511287798066],
+    );
+    assert_approx(&tilejson.center.unwrap(), &[0.0, 0.0, 0.0]);
+
+    let json = serde_json::to_value(&tilejson).unwrap();
+    assert_eq!(
+        json["tiles"][0],
+        "https://example.com/tiles/{z}/{x}/{y}.pbf"
+    );
+    assert!(json.get("vector_layers").is_none());
+    assert!(json.get("name").is_none());
+}
+
+#[test]
+fn test_tilejson_extent() {
+    let mercator = tms().lookup("WebMercatorQuad").unwrap();
+    let mut layer = VectorLayer::new("roads");
+    layer
+        .fields
+        .insert("class".to_string(), "String".to_string());
+    layer.maxzoom = Some(14);
+    let tilejson = TileJsonBuilder::new(&mercator)
+        .tiles([
+            "https://a.example.com/{z}/{x}/{y}.pbf",
+            "https://b.example.com/{z}/{x}/{y}.pbf",
+        ])
+        .extent(BoundingBox::new(-20.0, 60.0, 40.0, 89.0))
+        .zoom_range(2, 14)
+        .vector_layers(vec![layer])
+        .name("roads")
+        .attribution("© contributors")
+        .version("1.0.0")
+        .build()
+        .unwrap();
+    assert_approx(
+        &tilejson.bounds.unwrap(),
+        &[-20.0, 60.0, 40.0, 85.0511287798066],
+    );
+    assert_eq!(tilejson.center.unwrap()[0], 10.0);
+    assert_eq!(tilejson.center.unwrap()[2], 2.0);
+
+    let json = serde_json::to_value(&tilejson).unwrap();
+    assert_eq!(json["tiles"].as_array().unwrap().len(), 2);
+    assert_eq!(json["vector_layers"][0]["id"], "roads");
+    assert_eq!(json["vector_layers"][0]["fields"]["class"], "String");
+    assert_eq!(json["vector_layers"][0]["maxzoom"], 14);
+    assert_eq!(json["minzoom"], 2);
+    assert_eq!(json["name"], "roads");
+    let parsed: TileJson = serde_json::from_value(json).unwrap();
+    assert_eq!(parsed, tilejson);
+
+    // Extent crossing the antimeridian
+    let tilejson = TileJsonBuilder::new(&mercator)
+        .tiles(["/{z}/{x}/{y}.png"])
+        .extent(BoundingBox::new(170.0, -20.0, -150.0, 0.0))
+        .center(Coords::new(179.0, -10.0), 5)
+        .build()
+        .unwrap();
+    assert_eq!(tilejson.bounds, Some([170.0, -20.0, -150.0, 0.0]));
+    assert_eq!(tilejson.center, Some([179.0, -10.0, 5.0]));
+    let tilejson = TileJsonBuilder::new(&mercator)
+        .tiles(["/{z}/{x}/{y}.png"])
+        .extent(BoundingBox::new(170.0, -20.0, -150.0, 0.0))
+        .build()
+        .unwrap();
+    assert_eq!(tilejson.center, Some([-170.0, -10.0, 0.0]));
+}
+
+#[test]
+fn test_tilejson_other_tms() {
+    let crs84 = tms().lookup("WorldCRS84Quad").unwrap();
+    let tilejson = TileJsonBuilder::new(&crs84)
+        .tiles(["/{z}/{x}/{y}.png"])
+        .build()
+        .unwrap();
+    assert_approx(&tilejson.bounds.unwrap(), &[-180.0, -90.0, 180.0, 90.0]);
+    assert_eq!(tilejson.minzoom, Some(crs84.minzoom()));
+
+    let mut data = tms().get("WebMercatorQuad").unwrap().clone();
+    for matrix in data.tile_matrices.iter_mut() {
+        matrix.corner_of_origin = Some(CornerOfOrigin::BottomLeft);
+        matrix.point_of_origin[1] = -matrix.point_of_origin[1];
+    }
+    let bottom_left = Tms::try_from(&data).unwrap();
+    let tilejson = TileJsonBuilder::new(&bottom_left)
+        .tiles(["/{z}/{x}/{y}.png"])
+        .build()
+        .unwrap();
+    assert_eq!(tilejson.scheme.as_deref(), Some("tms"));
+}
+
+#[test]
+fn test_tilejson_placeholders() {
+    let mercator = tms().lookup("WebMercatorQuad").unwrap();
+    let tiles = [
+        "https://{s}.example.com/{z}/{x}/{y}{r}.png",
+        "/tms/{z}/{x}/{-y}.png?key={key}",
+    ];
+    let tilejson = TileJsonBuilder::new(&mercator)
+        .tiles(tiles)
+        .build()
+        .unwrap();
+    assert_eq!(tilejson.tiles, tiles);
+}
+
+#[test]
+fn test_tilejson_errors() {
+    let mercator = tms().lookup("WebMercatorQuad").unwrap();
+    assert!(matches!(
+        TileJsonBuilder::new(&mercator).build(),
+        Err(TmsError::UrlTemplateError(_))
+    ));
+    assert!(matches!(
+        TileJsonBuilder::new(&mercator)
+            .tiles(["/{z}/{col}/{y}"])
+            .build(),
+        Err(TmsError::UrlTemplateError(_))
+    ));
+    assert!(TileJsonBuilder::new(&mercator)
+        .tiles(["/{z}/{x}/{y}", "/{z}/{x}.png"])
+        .build()
+        .is_err());
+    assert!(matches!(
+        TileJsonBuilder::new(&mercator)
+            .tiles(["/{z}/{x}/{y}"])
+            .zoom_range(5, 2)
+            .build(),
+        Err(TmsError::InvalidZoom(5))
+    ));
+}
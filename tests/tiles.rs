@@ -1,10 +1,11 @@
+use ogcapi_types::common::{Authority, Crs};
 use std::iter::zip;
 use tile_grid::*;
 
 #[cfg(feature = "projtransform")]
-const DEFAULT_GRID_COUNT: usize = 8;
+const DEFAULT_GRID_COUNT: usize = 10;
 #[cfg(not(feature = "projtransform"))]
-const DEFAULT_GRID_COUNT: usize = 3;
+const DEFAULT_GRID_COUNT: usize = 8;
 
 #[test]
 fn test_default_grids() {
@@ -78,11 +79,51 @@ fn test_tms_properties() {
     // Test TileSchema().
     let tms = tms().lookup("WebMercatorQuad").unwrap();
     assert_eq!(tms.crs().to_urn(), "urn:ogc:def:crs:EPSG:0:3857");
-    assert_eq!(meters_per_unit(tms.crs()), 1.0);
+    assert_eq!(meters_per_unit(tms.crs()).unwrap(), 1.0);
     assert_eq!(tms.minzoom(), 0);
     assert_eq!(tms.maxzoom(), 24);
 }
 
+#[test]
+fn test_crs_units() {
+    assert_eq!(
+        meters_per_unit(&Crs::from_epsg(4258)).unwrap(),
+        2.0 * std::f64::consts::PI * 6378137.0 / 360.0
+    );
+    assert_eq!(meters_per_unit(&Crs::from_epsg(3035)).unwrap(), 1.0);
+    assert_eq!(
+        meters_per_unit(&Crs::from_epsg(2263)).unwrap(),
+        0.30480060960121924
+    );
+
+    let mut tms = tms().get("WebMercatorQuad").unwrap().clone();
+    tms.ordered_axes = None;
+    assert!(!tms.crs_axis_inverted());
+    tms.crs = Crs::from_epsg(4258);
+    assert!(tms.crs_axis_inverted());
+
+    // Foot-based State Plane CRS
+    let tms = Tms::custom(
+        vec![900000.0, 100000.0, 1100000.0, 300000.0],
+        &Crs::from_epsg(2263),
+        256,
+        256,
+        None,
+        None,
+        0,
+        1,
+        "NY Long Island",
+        "NYLongIsland",
+        None,
+        &Crs::default(),
+    )
+    .unwrap();
+    let matrix = tms.matrix(0).unwrap();
+    let res = tms.resolution(matrix.as_ref());
+    assert_eq!(res, 200000.0 / 256.0);
+    assert!((matrix.as_ref().scale_denominator - res * 0.30480060960121924 / 0.00028).abs() < 1e-6);
+}
+
 #[test]
 fn test_tile_coordinates() {
     // Test coordinates to tile index utils.
@@ -121,7 +162,7 @@ fn test_xy_bounds() {
         7044436.526761846,
     ];
     let tms = tms().lookup("WebMercatorQuad").unwrap();
-    let bounds = tms.xy_bounds(&Xyz::new(486, 332, 10));
+    let bounds = tms.xy_bounds(&Xyz::new(486, 332, 10)).unwrap();
     for (a, b) in zip(
         expected,
         [bounds.left, bounds.bottom, bounds.right, bounds.top],
@@ -149,7 +190,7 @@ fn round_to_prec(number: f64, precision: u8) -> f64 {
 fn test_projul_tile() {
     // TileMatrixSet._ul should return the correct coordinates in input projection.
     let tms = tms().lookup("WebMercatorQuad").unwrap();
-    let xy = tms.xy_ul(&Xyz::new(486, 332, 10));
+    let xy = tms.xy_ul(&Xyz::new(486, 332, 10)).unwrap();
     let expected = [-1017529.7205322663, 7044436.526761846];
     for (a, b) in zip(expected, [xy.x, xy.y]) {
         assert_eq!(round_to_prec(a - b, 7).abs(), 0.0);
@@ -166,33 +207,74 @@ fn test_projtile() {
 #[test]
 fn test_feature() {
     // TileSchema.feature should create proper geojson feature.
-    // tms = morecantile.tms.get("WebMercatorQuad")
-    // feat = tms.feature(morecantile.Tile(1, 0, 1))
-    // assert feat["bbox"]
-    // assert feat["id"]
-    // assert feat["geometry"]
-    // assert len(feat["properties"].keys()) == 3
-
-    // feat = tms.feature(
-    //     morecantile.Tile(1, 0, 1),
-    //     buffer=-10,
-    //     precision=4,
-    //     fid="1",
-    //     props={"some": "thing"},
-    // )
-    // assert feat["bbox"]
-    // assert feat["id"] == "1"
-    // assert feat["geometry"]
-    // assert len(feat["properties"].keys()) == 4
-
-    // with pytest.warns(UserWarning):
-    //     feat = tms.feature(
-    //         morecantile.Tile(1, 0, 1), projected=True, fid="1", props={"some": "thing"}
-    //     )
-    // assert feat["bbox"]
-    // assert feat["id"] == "1"
-    // assert feat["geometry"]
-    // assert len(feat["properties"].keys()) == 4
+    let tms = tms().lookup("WebMercatorQuad").unwrap();
+    let feat = tms
+        .feature(&Xyz::new(1, 0, 1), None, None, None, None, false)
+        .unwrap();
+    assert_eq!(feat["bbox"][2], 180.0);
+    assert_eq!(feat["id"], "Tile(x=1, y=0, z=1)");
+    assert_eq!(
+        feat["geometry"]["coordinates"][0][0],
+        serde_json::json!([0.0, 0.0])
+    );
+    assert_eq!(feat["properties"].as_object().unwrap().len(), 3);
+    assert_eq!(feat["properties"]["grid_name"], "WebMercatorQuad");
+
+    let mut props = serde_json::Map::new();
+    props.insert("some".to_string(), "thing".into());
+    let feat = tms
+        .feature(
+            &Xyz::new(1, 0, 1),
+            Some("1"),
+            Some(props.clone()),
+            Some(-10.0),
+            Some(4),
+            false,
+        )
+        .unwrap();
+    assert_eq!(
+        feat["bbox"],
+        serde_json::json!([10.0, 10.0, 170.0, 75.0511])
+    );
+    assert_eq!(feat["id"], "1");
+    assert!(feat["geometry"].is_object());
+    assert_eq!(feat["properties"].as_object().unwrap().len(), 4);
+
+    let feat = tms
+        .feature(
+            &Xyz::new(1, 0, 1),
+            Some("1"),
+            Some(props),
+            None,
+            Some(2),
+            true,
+        )
+        .unwrap();
+    assert_eq!(
+        feat["bbox"],
+        serde_json::json!([0.0, 0.0, 20037508.34, 20037508.34])
+    );
+    assert_eq!(feat["id"], "1");
+    assert_eq!(feat["crs"]["properties"]["code"], 3857);
+    assert_eq!(feat["properties"].as_object().unwrap().len(), 4);
+
+    // CRS without EPSG code
+    let mut data = tms.tms.clone();
+    data.crs = Crs::new(Authority::OGC, "1.3", "AUTO42001");
+    let auto = Tms::try_from(&data).unwrap();
+    let feat = auto
+        .feature(&Xyz::new(1, 0, 1), None, None, None, None, true)
+        .unwrap();
+    assert_eq!(
+        feat["crs"],
+        serde_json::json!({"type": "name", "properties": {"name": "urn:ogc:def:crs:OGC:1.3:AUTO42001"}})
+    );
+
+    let fc = tms
+        .feature_collection(tms.xyz_iterator(&tms.xy_bbox(), 0, 1).unwrap(), false)
+        .unwrap();
+    assert_eq!(fc["type"], "FeatureCollection");
+    assert_eq!(fc["features"].as_array().unwrap().len(), 5);
 }
 
 #[test]
@@ -256,7 +338,6 @@ fn test_xy_north_pole() {
 }
 
 #[test]
-#[cfg(feature = "projtransform")]
 fn test_xy_truncate() {
     // Input is truncated
     let tms = tms().lookup("WebMercatorQuad").unwrap();
@@ -297,14 +378,13 @@ fn test_tile_truncate() {
 }
 
 #[test]
-#[cfg(feature = "projtransform")]
 fn test_tiles() {
     // Test tiles from bbox.
     let tms = tms().lookup("WebMercatorQuad").unwrap();
 
     let bounds = (-105.0, 39.99, -104.99, 40.0);
     let tiles = tms
-        .tiles(bounds.0, bounds.1, bounds.2, bounds.3, &vec![14], false)
+        .tiles(bounds.0, bounds.1, bounds.2, bounds.3, &[14], false)
         .unwrap();
     let expect = vec![Xyz::new(3413, 6202, 14), Xyz::new(3413, 6203, 14)];
     assert_eq!(tiles.collect::<Vec<Xyz>>(), expect);
@@ -312,29 +392,29 @@ fn test_tiles() {
     // Single zoom
     let bounds = (-105.0, 39.99, -104.99, 40.0);
     let tiles = tms
-        .tiles(bounds.0, bounds.1, bounds.2, bounds.3, &vec![14], false)
+        .tiles(bounds.0, bounds.1, bounds.2, bounds.3, &[14], false)
         .unwrap();
     let expect = vec![Xyz::new(3413, 6202, 14), Xyz::new(3413, 6203, 14)];
     assert_eq!(tiles.collect::<Vec<Xyz>>(), expect);
 
     // Input is truncated
     assert_eq!(
-        tms.tiles(-181.0, 0.0, -170.0, 10.0, &vec![2], true)
+        tms.tiles(-181.0, 0.0, -170.0, 10.0, &[2], true)
             .unwrap()
             .collect::<Vec<Xyz>>(),
-        tms.tiles(-180.0, 0.0, -170.0, 10.0, &vec![2], false)
+        tms.tiles(-180.0, 0.0, -170.0, 10.0, &[2], false)
             .unwrap()
             .collect::<Vec<Xyz>>()
     );
 
     assert_eq!(
-        tms.tiles(-180.0, -90.0, 180.0, 90.0, &vec![0], false)
+        tms.tiles(-180.0, -90.0, 180.0, 90.0, &[0], false)
             .unwrap()
             .collect::<Vec<Xyz>>(),
         vec![Xyz::new(0, 0, 0)]
     );
     assert_eq!(
-        tms.tiles(-180.0, -90.0, 180.0, 90.0, &vec![0], false)
+        tms.tiles(-180.0, -90.0, 180.0, 90.0, &[0], false)
             .unwrap()
             .collect::<Vec<Xyz>>(),
         vec![Xyz::new(0, 0, 0)]
@@ -343,7 +423,7 @@ fn test_tiles() {
     // Antimeridian-crossing bounding boxes are handled
     let bounds = (175.0, 5.0, -175.0, 10.0);
     assert_eq!(
-        tms.tiles(bounds.0, bounds.1, bounds.2, bounds.3, &vec![2], false)
+        tms.tiles(bounds.0, bounds.1, bounds.2, bounds.3, &[2], false)
             .unwrap()
             .count(),
         2
@@ -370,8 +450,8 @@ fn test_extend_zoom() {
     // TileMatrixSet.ul should return the correct coordinates.
     let tms = tms().lookup("WebMercatorQuad").unwrap();
 
-    let merc = tms.xy_bounds(&Xyz::new(1000, 1000, 25));
-    let more = tms.xy_bounds(&Xyz::new(1000, 1000, 25));
+    let merc = tms.xy_bounds(&Xyz::new(1000, 1000, 25)).unwrap();
+    let more = tms.xy_bounds(&Xyz::new(1000, 1000, 25)).unwrap();
     for (a, b) in zip(
         [more.left, more.bottom, more.right, more.top],
         [merc.left, merc.bottom, merc.right, merc.top],
@@ -379,8 +459,8 @@ fn test_extend_zoom() {
         assert_eq!(round_to_prec(a - b, 7), 0.0);
     }
 
-    let merc = tms.xy_bounds(&Xyz::new(2000, 2000, 26));
-    let more = tms.xy_bounds(&Xyz::new(2000, 2000, 26));
+    let merc = tms.xy_bounds(&Xyz::new(2000, 2000, 26)).unwrap();
+    let more = tms.xy_bounds(&Xyz::new(2000, 2000, 26)).unwrap();
     for (a, b) in zip(
         [more.left, more.bottom, more.right, more.top],
         [merc.left, merc.bottom, merc.right, merc.top],
@@ -388,8 +468,8 @@ fn test_extend_zoom() {
         assert_eq!(round_to_prec(a - b, 7), 0.0);
     }
 
-    let merc = tms.xy_bounds(&Xyz::new(2000, 2000, 27));
-    let more = tms.xy_bounds(&Xyz::new(2000, 2000, 27));
+    let merc = tms.xy_bounds(&Xyz::new(2000, 2000, 27)).unwrap();
+    let more = tms.xy_bounds(&Xyz::new(2000, 2000, 27)).unwrap();
     for (a, b) in zip(
         [more.left, more.bottom, more.right, more.top],
         [merc.left, merc.bottom, merc.right, merc.top],
@@ -397,8 +477,8 @@ fn test_extend_zoom() {
         assert_eq!(round_to_prec(a - b, 7), 0.0);
     }
 
-    let merc = tms.xy_bounds(&Xyz::new(2000, 2000, 30));
-    let more = tms.xy_bounds(&Xyz::new(2000, 2000, 30));
+    let merc = tms.xy_bounds(&Xyz::new(2000, 2000, 30)).unwrap();
+    let more = tms.xy_bounds(&Xyz::new(2000, 2000, 30)).unwrap();
     for (a, b) in zip(
         [more.left, more.bottom, more.right, more.top],
         [merc.left, merc.bottom, merc.right, merc.top],
@@ -495,3 +575,60 @@ fn test_children_multi() {
 // fn test_children_invalid_zoom() {
 //     // invalid zoom.
 // }
+
+#[test]
+fn test_xyz_value() {
+    let tile: Xyz = "10/486/332".parse().unwrap();
+    assert_eq!(tile, Xyz::new(486, 332, 10));
+    assert_eq!(tile.to_string(), "10/486/332");
+    for invalid in ["10/486", "10/486/332/1", "256/0/0", "1/-1/0", "a/b/c", ""] {
+        assert!(matches!(
+            invalid.parse::<Xyz>(),
+            Err(TmsError::TileArgParsingError(_))
+        ));
+    }
+
+    let mut tiles = vec![Xyz::new(1, 0, 1), Xyz::new(0, 1, 1), Xyz::new(5, 5, 0)];
+    tiles.sort();
+    assert_eq!(
+        tiles,
+        [Xyz::new(5, 5, 0), Xyz::new(0, 1, 1), Xyz::new(1, 0, 1)]
+    );
+
+    let mut counts = std::collections::HashMap::new();
+    *counts.entry(tile).or_insert(0) += 1;
+    *counts.entry(Xyz::new(486, 332, 10)).or_insert(0) += 1;
+    assert_eq!(counts[&tile], 2);
+
+    let json = serde_json::to_string(&tile).unwrap();
+    assert_eq!(json, r#"{"x":486,"y":332,"z":10}"#);
+    assert_eq!(serde_json::from_str::<Xyz>(&json).unwrap(), tile);
+}
+
+#[test]
+fn test_bbox_ops() {
+    let bbox = BoundingBox::new(0.0, 0.0, 10.0, 20.0);
+    assert_eq!(bbox.width(), 10.0);
+    assert_eq!(bbox.height(), 20.0);
+    assert_eq!(bbox.center(), Coords::new(5.0, 10.0));
+    assert!(bbox.contains(&Coords::new(10.0, 0.0)));
+    assert!(!bbox.contains(&Coords::new(10.1, 0.0)));
+    assert_eq!(bbox.to_string(), "0,0,10,20");
+
+    let other = BoundingBox::new(5.0, -5.0, 15.0, 5.0);
+    assert_eq!(
+        bbox.intersection(&other),
+        Some(BoundingBox::new(5.0, 0.0, 10.0, 5.0))
+    );
+    assert_eq!(bbox.union(&other), BoundingBox::new(0.0, -5.0, 15.0, 20.0));
+    // Touching boxes don't intersect
+    let touching = BoundingBox::new(10.0, 0.0, 20.0, 20.0);
+    assert!(!bbox.intersects(&touching));
+    assert_eq!(bbox.intersection(&touching), None);
+
+    assert_eq!(bbox.buffer(1.0), BoundingBox::new(-1.0, -1.0, 11.0, 21.0));
+    assert_eq!(bbox.buffer(-1.0).width(), 8.0);
+
+    let json = serde_json::to_string(&bbox).unwrap();
+    assert_eq!(serde_json::from_str::<BoundingBox>(&json).unwrap(), bbox);
+}
@@ -1,6 +1,6 @@
 use core::num::{NonZeroU16, NonZeroU64};
 use ogcapi_types::common::{Authority::EPSG, Crs};
-use ogcapi_types::tiles::{TileMatrix, TileMatrixSet, TitleDescriptionKeywords};
+use ogcapi_types::tiles::{DataType, TileMatrix, TileMatrixSet, TitleDescriptionKeywords};
 use std::path::{Path, PathBuf};
 use tile_grid::*;
 
@@ -12,10 +12,6 @@ fn test_tile_matrix_set() {
         .filter_map(|entry| entry.ok())
         .map(|entry| entry.path())
         .filter(|p| p.extension().unwrap_or_default() == "json")
-        .filter(|p| {
-            !vec!["./data/CDB1GlobalGrid.json", "./data/GNOSISGlobalGrid.json"]
-                .contains(&p.as_os_str().to_str().unwrap())
-        })
         .collect::<Vec<_>>();
     assert!(tilesets.contains(&PathBuf::from("./data/WebMercatorQuad.json")));
 
@@ -30,7 +26,7 @@ fn test_tile_matrix_set() {
             .try_into()
             .unwrap();
         // This would fail if `supportedCRS` isn't supported by PROJ
-        assert!(tms.crs().as_known_crs().len() > 0);
+        assert!(!tms.crs().as_known_crs().is_empty());
     }
 }
 
@@ -70,11 +66,10 @@ fn test_zoom_for_res() {
     );
 
     // theoritical resolution of zoom 25 is 0.004665345964671392
-    // (morecantile emits warning and returns 25)
     assert_eq!(
         tms.zoom_for_res(0.0001, Some(25), &ZoomLevelStrategy::Auto, None)
             .unwrap(),
-        24
+        25
     );
 
     // minzoom greater than 0
@@ -163,18 +158,521 @@ fn test_tile_matrix_iter() {
 //     assert!(variable_matrix.validate().is_err());
 // }
 
+#[test]
+fn test_variable_matrix_widths() {
+    let tms = tms().lookup("GNOSISGlobalGrid").unwrap();
+    assert!(!tms.is_quadtree);
+
+    // Row 0 of zoom 1 coalesces 2 tiles
+    assert_eq!(tms.xy_tile(-100.0, 80.0, 1).unwrap(), Xyz::new(0, 0, 1));
+    assert_eq!(tms.xy_tile(-100.0, 30.0, 1).unwrap(), Xyz::new(1, 1, 1));
+    assert_eq!(
+        tms.xy_bounds(&Xyz::new(0, 0, 1)).unwrap(),
+        BoundingBox::new(-180.0, 45.0, -90.0, 90.0)
+    );
+    assert_eq!(
+        tms.xy_bounds(&Xyz::new(1, 1, 1)).unwrap(),
+        BoundingBox::new(-135.0, 0.0, -90.0, 45.0)
+    );
+
+    assert!(tms.is_valid(&Xyz::new(2, 0, 1)));
+    assert!(!tms.is_valid(&Xyz::new(1, 0, 1)));
+    assert!(tms.is_valid(&Xyz::new(1, 1, 1)));
+
+    let mut neighbors = tms.neighbors(&Xyz::new(2, 0, 1));
+    neighbors.sort_by_key(|t| (t.y, t.x));
+    assert_eq!(
+        neighbors,
+        vec![
+            Xyz::new(0, 0, 1),
+            Xyz::new(4, 0, 1),
+            Xyz::new(1, 1, 1),
+            Xyz::new(2, 1, 1),
+            Xyz::new(3, 1, 1),
+            Xyz::new(4, 1, 1),
+        ]
+    );
+
+    // Coalesced parent at zoom 1 of tiles in row 1 at zoom 2
+    assert_eq!(
+        tms.parent(&Xyz::new(2, 1, 2), None).unwrap(),
+        vec![Xyz::new(0, 0, 1)]
+    );
+    assert_eq!(
+        tms.children(&Xyz::new(0, 0, 1), None).unwrap(),
+        vec![Xyz::new(0, 0, 2), Xyz::new(0, 1, 2), Xyz::new(2, 1, 2)]
+    );
+
+    // 4 + 8 + 8 + 4 tiles
+    assert_eq!(tms.xyz_iterator(&tms.xy_bbox(), 1, 1).unwrap().count(), 24);
+    let cells = tms
+        .xyz_iterator(&BoundingBox::new(-130.0, 50.0, -50.0, 85.0), 2, 2)
+        .unwrap()
+        .collect::<Vec<_>>();
+    assert_eq!(
+        cells,
+        vec![
+            Xyz::new(0, 0, 2),
+            Xyz::new(2, 1, 2),
+            Xyz::new(4, 0, 2),
+            Xyz::new(4, 1, 2),
+        ]
+    );
+}
+
+#[test]
+fn test_register_dir() {
+    let dir = std::env::temp_dir().join("tile-grid-test-register-dir");
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(&dir).unwrap();
+    let custom = std::fs::read_to_string("./data/WorldCRS84Quad.json")
+        .unwrap()
+        .replace(r#""id": "WorldCRS84Quad""#, r#""id": "MyCustomGrid""#);
+    std::fs::write(dir.join("MyCustomGrid.json"), custom).unwrap();
+    std::fs::copy(
+        "./data/WebMercatorQuad.json",
+        dir.join("WebMercatorQuad.json"),
+    )
+    .unwrap();
+    std::fs::write(dir.join("Invalid.json"), "{}").unwrap();
+    std::fs::write(dir.join("README.md"), "Not a TileMatrixSet").unwrap();
+
+    let mut registry = TileMatrixSets::clone(&tms());
+    let errors = registry.register_dir(&dir, false).unwrap();
+    assert_eq!(errors.len(), 2);
+    assert_eq!(errors[0].0, dir.join("Invalid.json"));
+    assert!(matches!(errors[0].1, Error::TileMatrixSetError(_)));
+    assert_eq!(errors[1].0, dir.join("WebMercatorQuad.json"));
+    assert!(matches!(errors[1].1, Error::TmsAlreadyRegistered(_)));
+    assert!(registry.lookup("MyCustomGrid").is_ok());
+
+    // Overwrite built-in TileMatrixSets
+    let errors = registry.register_dir(&dir, true).unwrap();
+    assert_eq!(errors.len(), 1);
+
+    assert!(registry.register_dir(dir.join("missing"), true).is_err());
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn test_global_registry() {
+    let mut custom = tms().get("WebMercatorQuad").unwrap().clone();
+    custom.id = "TenantGrid".to_string();
+
+    let handles = (0..4)
+        .map(|_| {
+            std::thread::spawn(|| {
+                for _ in 0..100 {
+                    assert!(tms().lookup("WebMercatorQuad").is_ok());
+                }
+            })
+        })
+        .collect::<Vec<_>>();
+    register_global(vec![custom.clone()], false).unwrap();
+    for handle in handles {
+        handle.join().unwrap();
+    }
+    assert!(tms().lookup("TenantGrid").is_ok());
+
+    // Replace
+    assert!(register_global(vec![custom.clone()], false).is_err());
+    custom.title_description_keywords.title = Some("Tenant".to_string());
+    register_global(vec![custom], true).unwrap();
+    assert_eq!(
+        tms()
+            .get("TenantGrid")
+            .unwrap()
+            .title_description_keywords
+            .title,
+        Some("Tenant".to_string())
+    );
+
+    // Remove
+    remove_global("TenantGrid").unwrap();
+    assert!(tms().lookup("TenantGrid").is_err());
+    assert!(remove_global("TenantGrid").is_err());
+}
+
+#[test]
+fn test_shared_lookup() {
+    fn assert_send_sync<T: Send + Sync>() {}
+    assert_send_sync::<Tms>();
+
+    let tms1 = tms().lookup("WebMercatorQuad").unwrap();
+    let tms2 = tms().lookup("WebMercatorQuad").unwrap();
+    assert!(std::sync::Arc::ptr_eq(&tms1, &tms2));
+
+    let bounds = std::thread::spawn(move || tms1.bounds(&Xyz::new(10, 10, 4)).unwrap())
+        .join()
+        .unwrap();
+    assert_eq!(bounds, tms2.bounds(&Xyz::new(10, 10, 4)).unwrap());
+}
+
+#[test]
+fn test_densified_bbox() {
+    // North pole inside bounds
+    let bbox = tms().lookup("UPSArcticWGS84Quad").unwrap().bbox().unwrap();
+    assert_eq!((bbox.left, bbox.right, bbox.top), (-180.0, 180.0, 90.0));
+    assert!(bbox.bottom < 60.0);
+    // South pole inside bounds
+    let bbox = tms()
+        .lookup("UPSAntarcticWGS84Quad")
+        .unwrap()
+        .bbox()
+        .unwrap();
+    assert_eq!((bbox.left, bbox.right, bbox.bottom), (-180.0, 180.0, -90.0));
+    assert!(bbox.top > -60.0);
+}
+
+#[test]
+fn test_bottom_left_origin() {
+    // WebMercatorQuad with rows counted from the bottom (like OSGeo TMS)
+    let top_left = tms().lookup("WebMercatorQuad").unwrap();
+    let mut value = serde_json::to_value(&top_left.tms).unwrap();
+    value["id"] = "WebMercatorQuadBottomLeft".into();
+    for matrix in value["tileMatrices"].as_array_mut().unwrap() {
+        matrix["cornerOfOrigin"] = "bottomLeft".into();
+        matrix["pointOfOrigin"][1] = (-20037508.3427892).into();
+    }
+    let data = TileMatrixSet::from_json(&value.to_string()).unwrap();
+    let mut registry = TileMatrixSets::new();
+    registry.register(vec![data], false).unwrap();
+    let bottom_left = registry.lookup("WebMercatorQuadBottomLeft").unwrap();
+
+    let flipped = |tile: &Xyz| Xyz::new(tile.x, (1 << tile.z) - 1 - tile.y, tile.z);
+    let tile = Xyz::new(486, 332, 10);
+    assert_eq!(
+        bottom_left.xy_tile(-9000000.0, 4000000.0, 10).unwrap(),
+        flipped(&top_left.xy_tile(-9000000.0, 4000000.0, 10).unwrap())
+    );
+    let assert_close = |a: BoundingBox, b: BoundingBox, eps: f64| {
+        assert!(
+            (a.left - b.left).abs() < eps
+                && (a.bottom - b.bottom).abs() < eps
+                && (a.right - b.right).abs() < eps
+                && (a.top - b.top).abs() < eps,
+            "{a:?} != {b:?}"
+        );
+    };
+    assert_close(
+        bottom_left.xy_bounds(&flipped(&tile)).unwrap(),
+        top_left.xy_bounds(&tile).unwrap(),
+        1e-6,
+    );
+    let ul = bottom_left.xy_ul(&flipped(&tile)).unwrap();
+    let expected = top_left.xy_ul(&tile).unwrap();
+    assert!((ul.x - expected.x).abs() < 1e-6 && (ul.y - expected.y).abs() < 1e-6);
+    assert_close(bottom_left.xy_bbox(), top_left.xy_bbox(), 1e-6);
+    assert_close(
+        bottom_left.bounds(&flipped(&tile)).unwrap(),
+        top_left.bounds(&tile).unwrap(),
+        1e-9,
+    );
+
+    let extent = BoundingBox::new(-9000000.0, 4000000.0, -8000000.0, 5000000.0);
+    let tiles = bottom_left
+        .xyz_iterator(&extent, 5, 6)
+        .unwrap()
+        .collect::<Vec<_>>();
+    let mut expected = top_left
+        .xyz_iterator(&extent, 5, 6)
+        .unwrap()
+        .map(|tile| flipped(&tile))
+        .collect::<Vec<_>>();
+    expected.sort_by_key(|tile| (tile.z, tile.x, tile.y));
+    assert_eq!(tiles, expected);
+    assert_eq!(
+        bottom_left.parent(&flipped(&tile), None).unwrap(),
+        vec![flipped(&top_left.parent(&tile, None).unwrap()[0])]
+    );
+}
+
 #[test]
 fn test_invalid_tms() {
     assert!(tms().get("ANotValidName").is_err());
     assert!(tms().lookup("ANotValidName").is_err());
 }
 
+#[test]
+fn test_invalid_zoom() {
+    let mut data = tms().get("WebMercatorQuad").unwrap().clone();
+    data.tile_matrices.drain(0..3);
+    let mut registry = TileMatrixSets::new();
+    registry.register(vec![data.clone()], false).unwrap();
+    let tms = registry.lookup("WebMercatorQuad").unwrap();
+    assert_eq!(tms.minzoom(), 3);
+    assert!(matches!(tms.matrix(2), Err(TmsError::InvalidZoomError(2))));
+    assert!(tms.xy_bounds(&Xyz::new(0, 0, 2)).is_err());
+    assert!(tms.xy_tile(0.0, 0.0, 1).is_err());
+    assert!(!tms.is_valid(&Xyz::new(0, 0, 2)));
+    assert!(tms.neighbors(&Xyz::new(0, 0, 2)).is_empty());
+    // Extrapolated beyond maxzoom
+    assert!(tms.matrix(30).is_ok());
+
+    data.tile_matrices.clear();
+    assert!(matches!(
+        registry.register(vec![data], true),
+        Err(Error::TmsError(TmsError::NoTileMatrices))
+    ));
+}
+
+#[test]
+fn test_extrapolated_matrix() {
+    let grid = tms().lookup("WebMercatorQuad").unwrap();
+    let m24 = grid.matrix(24).unwrap().as_ref().clone();
+    for z in 25..=30u8 {
+        let matrix = grid.matrix(z).unwrap();
+        let matrix = matrix.as_ref();
+        let factor = 2f64.powi(i32::from(z - 24));
+        assert_eq!(matrix.id, z.to_string());
+        assert!((matrix.cell_size - m24.cell_size / factor).abs() < 1e-12);
+        assert!((matrix.scale_denominator - m24.scale_denominator / factor).abs() < 1e-6);
+        assert_eq!(u64::from(matrix.matrix_width), 1 << z);
+        assert_eq!(u64::from(matrix.matrix_height), 1 << z);
+        assert!((grid.resolution(matrix) - matrix.cell_size).abs() < 1e-9);
+    }
+    let bounds = grid.xy_bounds(&Xyz::new(0, 0, 25)).unwrap();
+    let tile_size = 2.0 * 20037508.3427892 / 2f64.powi(25);
+    assert!((bounds.right - bounds.left - tile_size).abs() < 1e-6);
+
+    // Non-uniform scale progression
+    let mut data = tms().get("WebMercatorQuad").unwrap().clone();
+    data.tile_matrices.truncate(4);
+    data.tile_matrices[3].scale_denominator /= 1.5;
+    data.tile_matrices[3].cell_size /= 1.5;
+    let grid = Tms::try_from(&data).unwrap();
+    assert!(grid.matrix(3).is_ok());
+    assert!(matches!(grid.matrix(4), Err(TmsError::VariableScale(4))));
+
+    // Single matrix
+    data.tile_matrices.truncate(1);
+    let grid = Tms::try_from(&data).unwrap();
+    assert!(matches!(grid.matrix(1), Err(TmsError::InvalidZoomError(1))));
+}
+
+#[test]
+fn test_non_numeric_ids() {
+    // CDB1GlobalGrid has identifiers from -10 to 21
+    let grid = tms().lookup("CDB1GlobalGrid").unwrap();
+    assert_eq!(grid.minzoom(), 0);
+    assert_eq!(grid.maxzoom(), 31);
+    assert_eq!(grid.matrix_id(0), Some("-10"));
+    assert_eq!(grid.matrix_id(10), Some("0"));
+    assert_eq!(grid.zoom_by_id("0"), Some(10));
+    assert_eq!(grid.zoom_by_id("22"), None);
+    assert_eq!(grid.matrix_by_id("-9").unwrap().tile_width.get(), 2);
+    assert_eq!(grid.matrix_z(1).unwrap().id, "-9");
+    let bounds = grid.xy_bounds(&Xyz::new(0, 0, 0)).unwrap();
+    assert_eq!(bounds.top, 90.0);
+
+    // Identifiers in arbitrary order
+    let mut data = tms().get("WebMercatorQuad").unwrap().clone();
+    data.tile_matrices.truncate(4);
+    for m in data.tile_matrices.iter_mut() {
+        m.id = format!("EPSG:3857:{}", m.id);
+    }
+    data.tile_matrices.swap(0, 2);
+    let grid = Tms::try_from(&data).unwrap();
+    assert!(grid.is_quadtree);
+    assert_eq!((grid.minzoom(), grid.maxzoom()), (0, 3));
+    assert_eq!(grid.matrix_id(2), Some("EPSG:3857:2"));
+    assert_eq!(grid.zoom_by_id("EPSG:3857:0"), Some(0));
+    assert_eq!(
+        grid.matrix_by_id("EPSG:3857:1").unwrap().matrix_width.get(),
+        2
+    );
+    assert_eq!(grid.matrix(4).unwrap().as_ref().id, "4");
+    assert_eq!(
+        grid.xy_bounds(&Xyz::new(1, 1, 1)).unwrap(),
+        tms()
+            .lookup("WebMercatorQuad")
+            .unwrap()
+            .xy_bounds(&Xyz::new(1, 1, 1))
+            .unwrap()
+    );
+    assert_eq!(grid.quadkey(&Xyz::new(3, 3, 2)).unwrap(), "33");
+
+    // Duplicate numeric identifiers
+    let mut data = tms().get("WebMercatorQuad").unwrap().clone();
+    data.tile_matrices[1].id = "0".to_string();
+    assert!(matches!(
+        Tms::try_from(&data),
+        Err(TmsError::InvalidZoomId(id)) if id == "0"
+    ));
+}
+
+#[test]
+fn test_tileset() {
+    let grid = tms().lookup("WebMercatorQuad").unwrap();
+    let extent = BoundingBox::new(5.9, 45.8, 10.5, 47.8);
+    let tileset = grid.tileset(DataType::Vector, &extent, 0, 8).unwrap();
+    let limits = tileset.tile_matrix_set_limits.as_ref().unwrap();
+    assert_eq!(limits.len(), 9);
+    assert_eq!(limits[0].tile_matrix, "0");
+    assert_eq!(
+        (limits[0].min_tile_col, limits[0].max_tile_col),
+        (limits[0].min_tile_row, limits[0].max_tile_row)
+    );
+    let z8 = &limits[8];
+    assert_eq!(z8.tile_matrix, "8");
+    assert_eq!((z8.min_tile_col, z8.max_tile_col), (132, 135));
+    assert_eq!((z8.min_tile_row, z8.max_tile_row), (89, 91));
+    let tiles = grid
+        .xyz_iterator_geographic(&extent, 8, 8)
+        .unwrap()
+        .collect::<Vec<_>>();
+    assert_eq!(tiles.first(), Some(&Xyz::new(132, 89, 8)));
+    assert_eq!(tiles.last(), Some(&Xyz::new(135, 91, 8)));
+
+    let json = serde_json::to_value(&tileset).unwrap();
+    assert_eq!(json["dataType"], "vector");
+    assert_eq!(json["crs"], "http://www.opengis.net/def/crs/EPSG/0/3857");
+    assert_eq!(
+        json["boundingBox"]["lowerLeft"],
+        serde_json::json!([5.9, 45.8])
+    );
+    assert_eq!(json["tileMatrixSetLimits"][8]["minTileCol"], 132);
+
+    // Extent crossing the antimeridian
+    let extent = BoundingBox::new(170.0, -20.0, -170.0, 20.0);
+    let tileset = grid.tileset(DataType::Map, &extent, 2, 2).unwrap();
+    let limits = tileset.tile_matrix_set_limits.unwrap();
+    assert_eq!(limits.len(), 1);
+    assert_eq!((limits[0].min_tile_col, limits[0].max_tile_col), (0, 3));
+    assert_eq!((limits[0].min_tile_row, limits[0].max_tile_row), (1, 2));
+}
+
+#[test]
+fn test_tile_limits() {
+    let grid = tms().lookup("WebMercatorQuad").unwrap();
+    let extent = BoundingBox::new(5.9, 45.8, 10.5, 47.8);
+    let limits = grid.tile_limits(&extent, 7, 8, 0).unwrap();
+    assert_eq!(limits.len(), 2);
+    let range = &limits[1];
+    assert_eq!((range.z(), range.x_min(), range.x_max()), (8, 132, 135));
+    assert_eq!((range.y_min(), range.y_max()), (89, 91));
+    assert_eq!(range.count(), 12);
+    assert_eq!(range.clone().into_iter().count(), 12);
+    assert!(range.contains(&Xyz::new(133, 90, 8)));
+    assert!(!range.contains(&Xyz::new(136, 90, 8)));
+    assert!(!range.contains(&Xyz::new(133, 92, 8)));
+    assert!(!range.contains(&Xyz::new(133, 90, 9)));
+
+    // Buffer clipped to the tile matrix
+    let buffered = grid.tile_limits(&extent, 8, 8, 1).unwrap();
+    assert_eq!((buffered[0].x_min(), buffered[0].x_max()), (131, 136));
+    assert_eq!((buffered[0].y_min(), buffered[0].y_max()), (88, 92));
+    let buffered = grid.tile_limits(&extent, 0, 0, 2).unwrap();
+    assert_eq!(buffered[0].count(), 1);
+
+    // Set operations
+    let other = TileRange::new(8, 134, 140, 80, 90, &grid).unwrap();
+    let intersection = range.intersection(&other).unwrap();
+    assert_eq!(
+        intersection,
+        TileRange::new(8, 134, 135, 89, 90, &grid).unwrap()
+    );
+    let union = range.union(&other).unwrap();
+    assert_eq!(union, TileRange::new(8, 132, 140, 80, 91, &grid).unwrap());
+    let disjoint = TileRange::new(8, 0, 1, 0, 1, &grid).unwrap();
+    assert!(range.intersection(&disjoint).is_none());
+    assert!(range.intersection(&limits[0]).is_none());
+    assert!(range.union(&limits[0]).is_none());
+    assert!(matches!(
+        TileRange::new(8, 2, 1, 0, 0, &grid),
+        Err(TmsError::InvalidTileRange(_))
+    ));
+    assert!(matches!(
+        TileRange::new(8, 0, 256, 0, 0, &grid),
+        Err(TmsError::InvalidTileRange(_))
+    ));
+
+    // OGC TileMatrixLimits
+    let matrix_limits = range.to_tile_matrix_limits(&grid).unwrap();
+    assert_eq!(matrix_limits.tile_matrix, "8");
+    assert_eq!(
+        serde_json::to_value(&matrix_limits).unwrap(),
+        serde_json::json!({"tileMatrix": "8", "minTileRow": 89, "maxTileRow": 91, "minTileCol": 132, "maxTileCol": 135})
+    );
+    assert_eq!(
+        TileRange::from_tile_matrix_limits(&matrix_limits, &grid).unwrap(),
+        *range
+    );
+    let mut invalid = matrix_limits;
+    invalid.max_tile_col = 256;
+    assert!(matches!(
+        TileRange::from_tile_matrix_limits(&invalid, &grid),
+        Err(TmsError::InvalidTileRange(_))
+    ));
+    invalid.tile_matrix = "L08".to_string();
+    assert!(matches!(
+        TileRange::from_tile_matrix_limits(&invalid, &grid),
+        Err(TmsError::InvalidZoomId(_))
+    ));
+
+    // Coalesced tiles
+    let grid = tms().lookup("CDB1GlobalGrid").unwrap();
+    let range = grid.matrix_range(0).unwrap();
+    assert_eq!(range.count(), range.clone().into_iter().count() as u64);
+    assert!(range.count() < 360 * 180);
+    assert!(range.contains(&Xyz::new(0, 0, 0)));
+    assert!(!range.contains(&Xyz::new(1, 0, 0)));
+    assert!(range.contains(&Xyz::new(1, 90, 0)));
+    let part = grid
+        .tile_limits(&BoundingBox::new(-178.5, -90.0, -174.5, 90.0), 0, 0, 0)
+        .unwrap()
+        .remove(0);
+    assert_eq!((part.x_min(), part.x_max()), (0, 11));
+    assert_eq!(part.count(), part.tiles().count() as u64);
+    let buffered = grid
+        .tile_limits(&BoundingBox::new(-170.5, 89.5, -169.5, 89.9), 0, 0, 1)
+        .unwrap();
+    assert_eq!(
+        buffered,
+        vec![TileRange::new(0, 0, 11, 0, 1, &grid).unwrap()]
+    );
+
+    // Columns of coalesced rows are aligned to coalesced tiles
+    assert!(TileRange::new(0, 12, 23, 0, 0, &grid).is_ok());
+    assert!(TileRange::new(0, 12, 24, 0, 5, &grid).is_ok());
+    assert!(TileRange::new(0, 1, 5, 40, 50, &grid).is_ok());
+    for (x_min, x_max) in [(1, 11), (0, 13)] {
+        assert!(matches!(
+            TileRange::new(0, x_min, x_max, 0, 5, &grid),
+            Err(TmsError::InvalidTileRange(_))
+        ));
+    }
+    let mut limits = range.to_tile_matrix_limits(&grid).unwrap();
+    assert_eq!(
+        TileRange::from_tile_matrix_limits(&limits, &grid).unwrap(),
+        range
+    );
+    limits.min_tile_col = 1;
+    assert!(TileRange::from_tile_matrix_limits(&limits, &grid).is_err());
+
+    // Set operations keep the columns aligned
+    let polar = TileRange::new(0, 0, 11, 0, 0, &grid).unwrap();
+    let equator = TileRange::new(0, 13, 13, 40, 40, &grid).unwrap();
+    assert_eq!(
+        polar.union(&equator),
+        Some(TileRange::new(0, 0, 23, 0, 40, &grid).unwrap())
+    );
+    assert_eq!(polar.intersection(&range), Some(polar.clone()));
+    let mercator = tms()
+        .lookup("WebMercatorQuad")
+        .unwrap()
+        .matrix_range(0)
+        .unwrap();
+    assert!(polar.union(&mercator).is_none());
+    assert!(polar.intersection(&mercator).is_none());
+}
+
 #[test]
 fn morecantile_examples() {
     let tms = tms().lookup("WebMercatorQuad").unwrap();
 
     // Get the bounds for tile Z=4, X=10, Y=10 in the input projection
-    let bounds = tms.xy_bounds(&Xyz::new(10, 10, 4));
+    let bounds = tms.xy_bounds(&Xyz::new(10, 10, 4)).unwrap();
     assert_eq!(
         bounds,
         BoundingBox::new(
@@ -203,21 +701,21 @@ fn morecantile_examples() {
 
     // Or using coordinates in input CRS
     let coord = tms.xy(159.31, -42.0).unwrap();
-    if cfg!(projtransform) {
+    if cfg!(feature = "projtransform") {
         assert_eq!((coord.x, coord.y), (17734308.078276414, -5160979.444049783));
     } else {
         //assert_eq!((coord.x, coord.y), (17734308.078276414, -5160979.444049781));
     }
 
-    let tile = tms.xy_tile(17734308.1, -5160979.4, 4);
+    let tile = tms.xy_tile(17734308.1, -5160979.4, 4).unwrap();
     assert_eq!(tile, Xyz::new(15, 10, 4));
 }
 
 #[test]
 fn mercator_tms() {
-    let tms = tms().get("WebMercatorQuad").unwrap();
+    let mercator = tms().lookup("WebMercatorQuad").unwrap();
     assert_eq!(
-        serde_json::to_string_pretty(&tms).unwrap(),
+        serde_json::to_string_pretty(&mercator.tms).unwrap(),
         serde_json::to_string_pretty(&web_mercator_quad()).unwrap()
     );
 }
@@ -1074,7 +1572,7 @@ fn custom_lv95() {
         lv95_json
     );
 
-    let bounds = custom_tms.xy_bounds(&Xyz::new(10, 4, 17)); // lake of Zurich
+    let bounds = custom_tms.xy_bounds(&Xyz::new(10, 4, 17)).unwrap(); // lake of Zurich
     assert_eq!(
         bounds,
         BoundingBox::new(2676000.0, 1222000.0, 2701600.0, 1247600.0)
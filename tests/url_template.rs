@@ -0,0 +1,173 @@
+use ogcapi_types::tiles::TileMatrixSet;
+use tile_grid::*;
+
+#[test]
+fn test_expand() {
+    let mercator = tms().lookup("WebMercatorQuad").unwrap();
+    let tile = Xyz::new(486, 332, 10);
+    let expand = |template: &str| {
+        TileUrlTemplate::new(template)
+            .unwrap()
+            .expand(&mercator, &tile)
+            .unwrap()
+    };
+
+    assert_eq!(expand("/tiles/{z}/{x}/{y}.pbf"), "/tiles/10/486/332.pbf");
+    assert_eq!(expand("/tms/{z}/{x}/{-y}.png"), "/tms/10/486/691.png");
+    assert_eq!(
+        expand("https://t.example.com/{quadkey}.jpeg"),
+        "https://t.example.com/0313102310.jpeg"
+    );
+    assert_eq!(
+        expand("/wms?BBOX={bbox}&WIDTH=256"),
+        "/wms?BBOX=-1017529.7205322459,7005300.768279806,-978393.9620502368,7044436.526761815&WIDTH=256"
+    );
+    assert_eq!(
+        expand("/wmts/{TileMatrixSet}/{TileMatrix}/{TileRow}/{TileCol}.png"),
+        "/wmts/WebMercatorQuad/10/332/486.png"
+    );
+    assert_eq!(
+        expand("/tiles/{tileMatrixSetId}/{tileMatrix}/{tileRow}/{tileCol}"),
+        "/tiles/WebMercatorQuad/10/332/486"
+    );
+
+    // Web Mercator bounds of a geographic tile
+    let crs84 = tms().lookup("WorldCRS84Quad").unwrap();
+    let template = TileUrlTemplate::new("{bbox}|{bbox-epsg-3857}").unwrap();
+    let url = template.expand(&crs84, &Xyz::new(1, 0, 0)).unwrap();
+    let coords = url
+        .split([',', '|'])
+        .map(|coord| coord.parse::<f64>().unwrap())
+        .collect::<Vec<_>>();
+    let expected = [
+        0.0,
+        -90.0,
+        180.0,
+        90.0,
+        0.0,
+        f64::NAN,
+        20037508.342789244,
+        f64::NAN,
+    ];
+    for (coord, expected) in coords.iter().zip(expected) {
+        if !expected.is_nan() {
+            assert!((coord - expected).abs() < 1e-6, "{url}");
+        }
+    }
+
+    // Non-numeric matrix identifiers
+    let cdb = tms().lookup("CDB1GlobalGrid").unwrap();
+    let template = TileUrlTemplate::new("/{TileMatrix}/{TileRow}/{TileCol}").unwrap();
+    assert_eq!(
+        template.expand(&cdb, &Xyz::new(3, 2, 10)).unwrap(),
+        "/0/2/3"
+    );
+}
+
+#[test]
+fn test_bottom_left_origin() {
+    let mercator = tms().lookup("WebMercatorQuad").unwrap();
+    let mut value = serde_json::to_value(&mercator.tms).unwrap();
+    value["id"] = "WebMercatorQuadBottomLeft".into();
+    for matrix in value["tileMatrices"].as_array_mut().unwrap() {
+        matrix["cornerOfOrigin"] = "bottomLeft".into();
+        matrix["pointOfOrigin"][1] = (-20037508.3427892).into();
+    }
+    let bottom_left =
+        Tms::try_from(&TileMatrixSet::from_json(&value.to_string()).unwrap()).unwrap();
+    // Rows of a bottom left matrix are counted from the bottom
+    let tile = Xyz::new(486, 691, 10);
+    let expand = |template: &str| {
+        TileUrlTemplate::new(template)
+            .unwrap()
+            .expand(&bottom_left, &tile)
+            .unwrap()
+    };
+
+    assert_eq!(expand("/tms/{z}/{x}/{y}.png"), "/tms/10/486/691.png");
+    assert_eq!(expand("/tiles/{z}/{x}/{-y}.pbf"), "/tiles/10/486/332.pbf");
+    assert_eq!(
+        expand("/wmts/{TileMatrix}/{TileRow}/{TileCol}.png"),
+        "/wmts/10/332/486.png"
+    );
+    assert_eq!(
+        expand("/tiles/{tileMatrix}/{tileRow}/{tileCol}"),
+        "/tiles/10/691/486"
+    );
+    for template in [
+        "/tiles/{z}/{x}/{-y}.pbf",
+        "/wmts/{TileMatrix}/{TileRow}/{TileCol}.png",
+        "/wmts/{TileMatrix}/{TileRow}/{TileCol}/{y}.png",
+    ] {
+        let template = TileUrlTemplate::new(template).unwrap();
+        let url = template.expand(&bottom_left, &tile).unwrap();
+        assert_eq!(template.parse(&bottom_left, &url).unwrap(), tile, "{url}");
+    }
+}
+
+#[test]
+fn test_parse() {
+    let mercator = tms().lookup("WebMercatorQuad").unwrap();
+    let tile = Xyz::new(486, 332, 10);
+    for template in [
+        "/tiles/{z}/{x}/{y}.pbf",
+        "/tms/{z}/{x}/{-y}.png",
+        "https://t.example.com/{quadkey}.jpeg",
+        "/wmts/{TileMatrixSet}/{TileMatrix}/{TileRow}/{TileCol}.png",
+        "{z}-{x}-{y}",
+        "/tiles/{z}/{x}/{y}?token=a/b",
+    ] {
+        let template = TileUrlTemplate::new(template).unwrap();
+        let url = template.expand(&mercator, &tile).unwrap();
+        assert_eq!(template.parse(&mercator, &url).unwrap(), tile, "{url}");
+    }
+
+    let cdb = tms().lookup("CDB1GlobalGrid").unwrap();
+    let template = TileUrlTemplate::new("/{TileMatrix}/{TileRow}/{TileCol}").unwrap();
+    assert_eq!(template.parse(&cdb, "/-10/0/0").unwrap(), Xyz::new(0, 0, 0));
+
+    let template = TileUrlTemplate::new("/tiles/{z}/{x}/{y}.pbf").unwrap();
+    for url in [
+        "/tiles/10/486/332.png",
+        "/tiles/10/486.pbf",
+        "/tiles/a/486/332.pbf",
+        "/tiles/10/486/332.pbf/",
+        "/tiles/1/2/0.pbf",
+        "/tiles/1/0/-1.pbf",
+    ] {
+        assert!(
+            matches!(
+                template.parse(&mercator, url),
+                Err(TmsError::UrlTemplateError(_))
+            ),
+            "{url}"
+        );
+    }
+    // Conflicting values
+    let template = TileUrlTemplate::new("/{z}/{x}/{y}/{quadkey}").unwrap();
+    assert!(template.parse(&mercator, "/1/0/0/0").is_ok());
+    assert!(template.parse(&mercator, "/1/0/0/1").is_err());
+    let template = TileUrlTemplate::new("/{TileMatrixSet}/{z}/{x}/{y}").unwrap();
+    assert!(template.parse(&mercator, "/WorldCRS84Quad/1/0/0").is_err());
+    assert!(TileUrlTemplate::new("/{x}{y}/{z}")
+        .unwrap()
+        .parse(&mercator, "/00/1")
+        .is_err());
+    assert!(TileUrlTemplate::new("/{bbox}")
+        .unwrap()
+        .parse(&mercator, "/0,0,1,1")
+        .is_err());
+}
+
+#[test]
+fn test_invalid_template() {
+    assert!(matches!(
+        TileUrlTemplate::new("/{z}/{col}/{y}"),
+        Err(TmsError::UrlTemplateError(_))
+    ));
+    assert!(TileUrlTemplate::new("/{z}/{x}/{y").is_err());
+    assert_eq!(
+        TileUrlTemplate::new("/{z}/{x}/{y}").unwrap().template(),
+        "/{z}/{x}/{y}"
+    );
+}